@@ -0,0 +1,87 @@
+pub use wgpu::{Backend, DeviceType};
+
+/// The class of the adapter used for rendering, e.g., for asserting in CI that the tests run
+/// on a real GPU or, conversely, on a software rasterizer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AdapterClass {
+    /// A GPU separate from the CPU.
+    Discrete,
+    /// A GPU integrated into the CPU, which usually consumes less power.
+    Integrated,
+    /// A virtual GPU, e.g., of a virtual machine or a remote desktop.
+    Virtual,
+    /// A software rasterizer running on the CPU, e.g., llvmpipe, SwiftShader or WARP.
+    Cpu,
+    /// The type of the adapter is unknown, e.g., on the web or on the GL backend.
+    Unknown,
+}
+
+impl AdapterClass {
+    /// Derives the class of the adapter from the given adapter information.
+    ///
+    /// # Arguments
+    /// * `info` - The information about the adapter.
+    pub fn of_adapter(info: &wgpu::AdapterInfo) -> Self {
+        match info.device_type {
+            wgpu::DeviceType::DiscreteGpu => AdapterClass::Discrete,
+            wgpu::DeviceType::IntegratedGpu => AdapterClass::Integrated,
+            wgpu::DeviceType::VirtualGpu => AdapterClass::Virtual,
+            wgpu::DeviceType::Cpu => AdapterClass::Cpu,
+            wgpu::DeviceType::Other => AdapterClass::Unknown,
+        }
+    }
+
+    /// Returns true if the adapter is a hardware GPU, i.e., a discrete or integrated GPU.
+    pub fn is_hardware(&self) -> bool {
+        matches!(self, AdapterClass::Discrete | AdapterClass::Integrated)
+    }
+
+    /// Returns true if the adapter is a software rasterizer running on the CPU.
+    pub fn is_software(&self) -> bool {
+        *self == AdapterClass::Cpu
+    }
+}
+
+/// The information about the adapter used for rendering, e.g., for showing the GPU in the UI
+/// or for disabling expensive effects on weak hardware.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AdapterDetails {
+    /// The name of the adapter, e.g., the name of the GPU model.
+    pub name: String,
+    /// The PCI id of the vendor, or zero if unknown.
+    pub vendor_id: u32,
+    /// The PCI id of the device, or zero if unknown.
+    pub device_id: u32,
+    /// The type of the adapter as reported by the backend.
+    pub device_type: DeviceType,
+    /// The class of the adapter derived from its type.
+    pub class: AdapterClass,
+    /// The backend used for rendering.
+    pub backend: Backend,
+    /// The name and the version of the driver, if reported by the backend.
+    pub driver: String,
+}
+
+impl AdapterDetails {
+    /// Extracts the details from the given adapter information.
+    ///
+    /// # Arguments
+    /// * `info` - The information about the adapter.
+    pub fn of_adapter(info: &wgpu::AdapterInfo) -> Self {
+        let driver = match (info.driver.is_empty(), info.driver_info.is_empty()) {
+            (false, false) => format!("{} {}", info.driver, info.driver_info),
+            (false, true) => info.driver.clone(),
+            (true, _) => info.driver_info.clone(),
+        };
+
+        Self {
+            name: info.name.clone(),
+            vendor_id: info.vendor,
+            device_id: info.device,
+            device_type: info.device_type,
+            class: AdapterClass::of_adapter(info),
+            backend: info.backend,
+            driver,
+        }
+    }
+}
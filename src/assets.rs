@@ -0,0 +1,107 @@
+use std::{
+    any::Any,
+    cell::Cell,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::{
+    control::CanvasEvent,
+    error::{Error, Result},
+};
+
+/// A decoded asset as it is handed to `EventHandler::asset_loaded`, which downcasts it to the
+/// type returned by the decoder.
+pub type Asset = Box<dyn Any + Send>;
+
+/// Identifies an asset requested by `CanvasControl::load_asset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AssetId(pub(crate) u64);
+
+/// A decoding job executed by one of the worker threads.
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A pool of worker threads decoding assets in the background. The decoded assets are sent
+/// back to the event loop, where they are handed to the handler for uploading them to the GPU.
+pub(crate) struct AssetLoader {
+    jobs: mpsc::Sender<Job>,
+    proxy: Option<EventLoopProxy<CanvasEvent>>,
+    next_id: Cell<u64>,
+}
+
+impl AssetLoader {
+    /// Spawns the worker threads. The threads terminate once the loader has been dropped and
+    /// all queued jobs are done.
+    ///
+    /// # Arguments
+    /// * `threads` - The number of worker threads.
+    /// * `proxy` - The proxy for sending the decoded assets to the event loop, if any.
+    pub fn new(threads: usize, proxy: Option<EventLoopProxy<CanvasEvent>>) -> Result<Self> {
+        if threads == 0 {
+            return Err(Error::Internal(
+                "At least one asset worker thread is required".to_string(),
+            ));
+        }
+
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for index in 0..threads {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("asset-worker-{}", index))
+                .spawn(move || loop {
+                    // the lock is released before the job is executed
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+                .map_err(|e| {
+                    Error::Internal(format!("Failed to spawn asset worker thread: {}", e))
+                })?;
+        }
+
+        Ok(Self {
+            jobs: sender,
+            proxy,
+            next_id: Cell::new(0),
+        })
+    }
+
+    /// Queues the given decoder for execution on a worker thread and returns the id of the
+    /// asset.
+    ///
+    /// # Arguments
+    /// * `decode` - The function decoding the asset.
+    pub fn load<F, T>(&self, decode: F) -> AssetId
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let id = AssetId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+
+        let proxy = self.proxy.clone();
+        let job: Job = Box::new(move || {
+            let asset = decode().map(|asset| Box::new(asset) as Asset);
+
+            // the event loop may have been closed in the meantime, which is fine
+            if let Some(proxy) = proxy {
+                let _ = proxy.send_event(CanvasEvent::AssetLoaded(id, asset));
+            }
+        });
+
+        // the workers only stop once the loader has been dropped
+        let _ = self.jobs.send(job);
+
+        id
+    }
+}
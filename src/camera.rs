@@ -0,0 +1,189 @@
+use std::f32::consts::FRAC_PI_2;
+
+use crate::event_handler::MouseButton;
+
+/// The margin keeping the pitch away from the poles, where the view direction would be
+/// parallel to the up vector.
+const PITCH_MARGIN: f32 = 0.01;
+
+/// A camera orbiting around a target, e.g., for model viewers. Dragging with the rotate button
+/// orbits around the target and scrolling zooms in and out. The camera is driven by forwarding
+/// the input callbacks of the event handler to the methods with the same names, and its view
+/// matrix can be written into a uniform buffer.
+#[derive(Clone, Debug)]
+pub struct OrbitCamera {
+    target: [f32; 3],
+    distance: f32,
+    yaw: f32,
+    pitch: f32,
+    min_distance: f32,
+    max_distance: f32,
+    /// The mouse button for orbiting. Defaults to the left button.
+    pub rotate_button: MouseButton,
+    /// The rotation in radians per logical pixel of cursor movement. Defaults to 0.01.
+    pub rotate_speed: f32,
+    /// The relative change of the distance per scrolled line. Defaults to 0.1.
+    pub zoom_speed: f32,
+    dragging: bool,
+    last_cursor: Option<(f64, f64)>,
+}
+
+impl OrbitCamera {
+    /// Creates a new camera looking at the target from the front, i.e., along the negative
+    /// z-axis.
+    ///
+    /// # Arguments
+    /// * `target` - The point the camera orbits around.
+    /// * `distance` - The distance of the camera to the target.
+    pub fn new(target: [f32; 3], distance: f32) -> Self {
+        Self {
+            target,
+            distance: distance.max(f32::EPSILON),
+            yaw: 0.0,
+            pitch: 0.0,
+            min_distance: f32::EPSILON,
+            max_distance: f32::MAX,
+            rotate_button: MouseButton::Left,
+            rotate_speed: 0.01,
+            zoom_speed: 0.1,
+            dragging: false,
+            last_cursor: None,
+        }
+    }
+
+    /// Returns the point the camera orbits around.
+    pub fn target(&self) -> [f32; 3] {
+        self.target
+    }
+
+    /// Sets the point the camera orbits around.
+    ///
+    /// # Arguments
+    /// * `target` - The new target.
+    pub fn set_target(&mut self, target: [f32; 3]) {
+        self.target = target;
+    }
+
+    /// Returns the distance of the camera to the target.
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    /// Sets the distance of the camera to the target, clamped to the distance limits.
+    ///
+    /// # Arguments
+    /// * `distance` - The new distance.
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Sets the range the distance is clamped to while zooming.
+    ///
+    /// # Arguments
+    /// * `min` - The minimum distance, which must be positive.
+    /// * `max` - The maximum distance.
+    pub fn set_distance_limits(&mut self, min: f32, max: f32) {
+        self.min_distance = min.max(f32::EPSILON);
+        self.max_distance = max.max(self.min_distance);
+        self.set_distance(self.distance);
+    }
+
+    /// Sets the orientation of the camera.
+    ///
+    /// # Arguments
+    /// * `yaw` - The rotation around the up axis in radians.
+    /// * `pitch` - The elevation above the horizontal plane in radians, which is clamped to
+    ///   just below +-90 degrees.
+    pub fn set_orientation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-FRAC_PI_2 + PITCH_MARGIN, FRAC_PI_2 - PITCH_MARGIN);
+    }
+
+    /// Forwards `EventHandler::cursor_move` to the camera, which orbits while dragging.
+    ///
+    /// # Arguments
+    /// * `x` - The x coordinate of the cursor in logical coordinates.
+    /// * `y` - The y coordinate of the cursor in logical coordinates.
+    pub fn cursor_move(&mut self, x: f64, y: f64) {
+        if let (true, Some((last_x, last_y))) = (self.dragging, self.last_cursor) {
+            let dx = (x - last_x) as f32 * self.rotate_speed;
+            let dy = (y - last_y) as f32 * self.rotate_speed;
+            self.set_orientation(self.yaw - dx, self.pitch + dy);
+        }
+
+        self.last_cursor = Some((x, y));
+    }
+
+    /// Forwards `EventHandler::mouse_button` to the camera, which starts or stops dragging.
+    ///
+    /// # Arguments
+    /// * `x` - The x coordinate of the cursor in logical coordinates.
+    /// * `y` - The y coordinate of the cursor in logical coordinates.
+    /// * `button` - The pressed/released mouse button.
+    /// * `pressed` - If true the mouse button was pressed and released otherwise.
+    pub fn mouse_button(&mut self, x: f64, y: f64, button: MouseButton, pressed: bool) {
+        if button == self.rotate_button {
+            self.dragging = pressed;
+            self.last_cursor = Some((x, y));
+        }
+    }
+
+    /// Forwards the vertical delta of `EventHandler::mouse_wheel` to the camera, which zooms in
+    /// for positive and out for negative deltas.
+    ///
+    /// # Arguments
+    /// * `delta` - The scrolled distance in lines.
+    pub fn scroll(&mut self, delta: f32) {
+        self.set_distance(self.distance * (1.0 - self.zoom_speed).powf(delta));
+    }
+
+    /// Returns the position of the camera.
+    pub fn eye(&self) -> [f32; 3] {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+
+        [
+            self.target[0] + self.distance * cos_pitch * sin_yaw,
+            self.target[1] + self.distance * sin_pitch,
+            self.target[2] + self.distance * cos_pitch * cos_yaw,
+        ]
+    }
+
+    /// Returns the right-handed view matrix with the y-axis pointing up, in column-major order
+    /// as expected by WGSL for a `mat4x4<f32>` uniform.
+    pub fn view_matrix(&self) -> [[f32; 4]; 4] {
+        let eye = self.eye();
+
+        let f = normalize(sub(self.target, eye));
+        let s = normalize(cross(f, [0.0, 1.0, 0.0]));
+        let u = cross(s, f);
+
+        [
+            [s[0], u[0], -f[0], 0.0],
+            [s[1], u[1], -f[1], 0.0],
+            [s[2], u[2], -f[2], 0.0],
+            [-dot(s, eye), -dot(u, eye), dot(f, eye), 1.0],
+        ]
+    }
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn normalize(a: [f32; 3]) -> [f32; 3] {
+    let length = dot(a, a).sqrt();
+    [a[0] / length, a[1] / length, a[2] / length]
+}
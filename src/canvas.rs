@@ -1,35 +1,622 @@
-use std::fmt::format;
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
+    time::{Duration, Instant},
+};
 
-use log::{debug, error, info};
+use log::{debug, error, info, log_enabled, warn, Level};
 use winit::{
-    dpi::{LogicalPosition, LogicalSize},
-    event::{ElementState, Event, WindowEvent},
-    event_loop::{ControlFlow, EventLoop},
-    window::{Window, WindowBuilder},
+    dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+    event::{
+        DeviceEvent, ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, Touch,
+        WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopWindowTarget},
+    keyboard::{KeyCode, ModifiersState, PhysicalKey},
+    window::{Fullscreen, Icon, Window, WindowBuilder, WindowId},
 };
 
 use crate::{
-    canvas,
+    clock::{MonotonicClock, TimeSource},
+    context::RenderContext,
+    control::{
+        check_scene_resolution, CanvasControl, CanvasController, CanvasEvent, MirrorRequest,
+    },
+    depth::{check_depth_format, DepthConfig},
     error::{Error, Result},
     event_handler::EventHandler,
+    gpu_timer::GpuTimer,
+    input::{CursorOrigin, InputEvent, InputState, PausedInput, SCROLL_LINE_HEIGHT},
+    mirror::Mirror,
+    offscreen::PreservedTarget,
+    profiling::{FrameProfiler, FrameProfiling, FrameStatsTracker, FrameTiming},
+    readback::{to_rgba, TextureReadback},
+    recovery::{DefaultErrorPolicy, ErrorPolicy, RecoveryAction},
+    scaling::{Scaler, ScalingMode, Viewport},
+    scene::SceneTarget,
+    texture::Texture,
+};
+
+#[cfg(feature = "assets")]
+use crate::assets::{Asset, AssetId};
+#[cfg(feature = "hot-reload")]
+use crate::hot_reload::ShaderWatcher;
+#[cfg(feature = "text")]
+use crate::text::TextOverlay;
+
+/// The color the frame is cleared to before `EventHandler::next_frame` is called.
+pub const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
 };
 
+/// The surface format requested for HDR output, see `CanvasOptions::hdr`.
+pub(crate) const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The maximum number of fixed updates per frame, see `CanvasOptions::fixed_timestep`. If the
+/// updates take longer than the timestep, the simulation falls behind instead of requiring
+/// more and more updates per frame.
+const MAX_FIXED_STEPS: u32 = 8;
+
+/// The behavior if the surface doesn't support any sRGB format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SrgbFallback {
+    /// Logs a warning and uses the first format supported by the surface.
+    #[default]
+    WarnAndFallback,
+    /// Creating the canvas fails with an error.
+    Error,
+    /// Always uses the first format supported by the surface without a warning, even if an
+    /// sRGB format is available, e.g., if the handler applies the gamma correction itself.
+    ForceFirst,
+}
+
+/// Determines when the canvas renders frames.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Frames are rendered continuously, e.g., for games and animations.
+    #[default]
+    Continuous,
+    /// The event loop waits for events and frames are only rendered when requested by
+    /// `CanvasControl::request_redraw` or by the platform, e.g., after a resize. This saves
+    /// power for editors and viewers, whose content only changes after input.
+    OnDemand,
+}
+
+impl RenderMode {
+    /// Returns the control flow of the event loop for the render mode.
+    fn control_flow(self) -> ControlFlow {
+        match self {
+            RenderMode::Continuous => ControlFlow::Poll,
+            RenderMode::OnDemand => ControlFlow::Wait,
+        }
+    }
+}
+
+/// Backend specific options for creating the wgpu instance.
+#[derive(Clone, Debug, Default)]
+pub struct BackendOptions {
+    /// The OpenGL ES 3 minor version to request on the GL backend, e.g., `Version0` for
+    /// compatibility with older GL contexts. Defaults to `Automatic`, i.e., the highest version
+    /// available.
+    pub gles_minor_version: wgpu::Gles3MinorVersion,
+}
+
+/// The icon of the window shown in the title bar and the taskbar.
+#[derive(Clone, Debug, Default)]
+pub struct WindowIcon {
+    /// The pixels of the icon as tightly packed 8-bit RGBA values, row by row from the top.
+    pub rgba: Vec<u8>,
+    /// The width of the icon in pixels.
+    pub width: u32,
+    /// The height of the icon in pixels.
+    pub height: u32,
+}
+
+impl WindowIcon {
+    /// Converts the icon into a winit icon after checking the size of the pixel data.
+    fn to_icon(&self) -> Result<Icon> {
+        let expected_len = self.width as u64 * self.height as u64 * 4;
+        if self.rgba.len() as u64 != expected_len {
+            return Err(Error::Internal(format!(
+                "Invalid window icon: expected {} bytes for {}x{} RGBA pixels, got {}",
+                expected_len,
+                self.width,
+                self.height,
+                self.rgba.len()
+            )));
+        }
+
+        Icon::from_rgba(self.rgba.clone(), self.width, self.height)
+            .map_err(|e| Error::Internal(format!("Invalid window icon: {}", e)))
+    }
+}
+
 /// The options for creating the canvas.
 pub struct CanvasOptions {
     pub width: u32,
     pub height: u32,
     pub title: String,
+
+    /// Determines if `width` and `height` are given in physical pixels or in logical pixels.
+    /// Logical pixels are scaled by the scale factor of the monitor, e.g., on a HiDPI display
+    /// with a scale factor of 2.0 a logical size of 800x600 results in a surface with 1600x1200
+    /// physical pixels. Physical pixels are used as they are, which makes the window appear
+    /// smaller on HiDPI displays. Defaults to false, i.e., logical pixels.
+    pub size_is_physical: bool,
+
+    /// The flags for the wgpu instance, e.g., for enabling validation and debug labels.
+    /// Defaults to `InstanceFlags::from_build_config()`, i.e., debugging and validation are
+    /// enabled in debug builds and disabled in release builds.
+    pub instance_flags: wgpu::InstanceFlags,
+
+    /// The backends the adapter may be chosen from, e.g., `Backends::VULKAN` for forcing Vulkan
+    /// over DX12 while debugging driver issues or for pinning a backend in CI. Creating the
+    /// canvas fails if none of the backends is available. Defaults to `Backends::all()`.
+    pub backends: wgpu::Backends,
+
+    /// The shader compiler used by the DX12 backend. FXC doesn't need any additional libraries,
+    /// whereas DXC supports newer shader models and compiles faster, but requires
+    /// `dxcompiler.dll` and `dxil.dll` to be available. Defaults to FXC, or to DXC with the
+    /// libraries loaded from the standard locations if the `dxc` feature is enabled.
+    /// Ignored on all other backends.
+    pub dx12_shader_compiler: wgpu::Dx12Compiler,
+
+    /// Backend specific options passed to the wgpu instance.
+    pub backend_options: BackendOptions,
+
+    /// Determines how the rendered content is mapped onto the surface. In the integer scaled
+    /// mode, the handler renders into a buffer with the fixed resolution of the mode, i.e.,
+    /// `setup` receives this resolution and `resize` isn't called when the window is resized.
+    /// In the letterboxed mode, `setup` and `resize` receive the size of the rectangle with the
+    /// aspect ratio of the mode. See `CanvasControl::content_viewport` for the position of the
+    /// content on the surface. Defaults to `ScalingMode::Native`.
+    pub scaling: ScalingMode,
+
+    /// The downlevel flags the adapter must support, e.g., for making sure that the application
+    /// doesn't rely on features missing on the GL backend. Creating the canvas fails with an
+    /// error listing the missing flags otherwise. Defaults to no flags.
+    pub required_downlevel_flags: wgpu::DownlevelFlags,
+
+    /// The minimum shader model the adapter must support. Defaults to `ShaderModel::Sm2`, i.e.,
+    /// any adapter is accepted.
+    pub required_shader_model: wgpu::ShaderModel,
+
+    /// The features the device is created with, e.g., `POLYGON_MODE_LINE` for wireframes or
+    /// `PUSH_CONSTANTS`. Creating the canvas fails with an error listing the missing features
+    /// if the adapter doesn't support all of them. Defaults to no features.
+    pub features: wgpu::Features,
+
+    /// The limits the device is created with, e.g., for more bind groups or push constants.
+    /// Creating the canvas fails if the adapter doesn't support them. Defaults to
+    /// `Limits::default()`.
+    pub limits: wgpu::Limits,
+
+    /// The directory a trace of all wgpu API calls is recorded into, e.g., for attaching it to
+    /// a bug report of a driver issue. Only recorded if the `trace` feature of wgpu is enabled
+    /// by the application, otherwise wgpu logs an error. Defaults to `None`, i.e., no trace.
+    pub trace_path: Option<PathBuf>,
+
+    /// The application id of the window, which is used by the desktop for grouping windows and
+    /// finding the icon. It should match the name of the `.desktop` file of the application.
+    /// Sets the `WM_CLASS` on X11 and the app id on Wayland. Ignored on all other platforms.
+    pub app_id: Option<String>,
+
+    /// The number of consecutive timeouts while acquiring the surface texture that are
+    /// tolerated by silently skipping the frame and retrying with the next one. Further
+    /// timeouts are reported as `Error::Timeout` to the handler and to
+    /// `ErrorPolicy::on_timeout`. A value of 0 reports every timeout. Defaults to 3.
+    pub max_consecutive_timeouts: u32,
+
+    /// If true, the contents of the previous frame are preserved and each frame is rendered on
+    /// top of it with `LoadOp::Load`, e.g., for incremental drawing. No backend guarantees that
+    /// the surface keeps its contents between frames, since the swapchain rotates through
+    /// several textures. Hence, the content is rendered into a persistent texture which is
+    /// copied onto the surface after each frame. The contents are cleared when the window is
+    /// resized. The scaled modes always render into a persistent texture.
+    /// Defaults to false.
+    pub preserve_contents: bool,
+
+    /// If true, the window is initially sized to fill the work area of the monitor, i.e., the
+    /// area not covered by task bars and other system bars. This is done by maximizing the
+    /// window, so the window manager determines the exact work area. Defaults to false.
+    pub fit_to_work_area: bool,
+
+    /// The number of samples per pixel of the render pass of `next_frame`, e.g., 4 for 4x
+    /// MSAA. If greater than 1, the pass renders into a multisampled texture, which is resolved
+    /// into the texture of the frame, and the depth buffer is multisampled as well. The
+    /// pipelines of the handler must use the same count, see `RenderContext::sample_count`.
+    /// Falls back to 1 with a warning if the adapter doesn't support the count for the
+    /// formats of the frame. Defaults to 1, i.e., no anti-aliasing.
+    pub sample_count: u32,
+
+    /// The depth buffer attached to the render pass of `next_frame`, e.g.,
+    /// `DepthConfig::standard(Depth32Float)` for 3D rendering. The depth buffer has the size
+    /// of the texture the handler renders into and is cleared to the clear value of the
+    /// configuration at the start of each frame, which must be in the range [0, 1]. The
+    /// configuration is available through `RenderContext::depth_config` for configuring the
+    /// depth state of the pipelines. Defaults to `None`, i.e., no depth buffer.
+    pub depth: Option<DepthConfig>,
+
+    /// The value the stencil buffer is cleared to at the start of each frame. Must fit into the
+    /// 8 bits of the stencil formats supported by wgpu. Defaults to 0.
+    pub clear_stencil: u32,
+
+    /// Determines what happens if the surface doesn't support any sRGB format. Writing linear
+    /// colors into a non-sRGB surface makes them appear darker. Defaults to
+    /// `SrgbFallback::WarnAndFallback`.
+    pub srgb_fallback: SrgbFallback,
+
+    /// If true, the HDR format `Rgba16Float` is requested for the surface, which the backends
+    /// pair with an extended-range linear sRGB color space where the platform supports it. The
+    /// handler writes linear colors, where values above 1.0 are brighter than SDR white. Falls
+    /// back to the SDR format selection with a warning if the surface doesn't support it, see
+    /// `CanvasControl::is_hdr`. Defaults to false.
+    pub hdr: bool,
+
+    /// The present mode of the surface, e.g., `Fifo` for vsync, which saves power, or
+    /// `Immediate` or `Mailbox` for uncapped frame rates. Falls back to the first mode
+    /// supported by the surface with a warning if the surface doesn't support it. Defaults to
+    /// `PresentMode::Fifo`.
+    pub present_mode: wgpu::PresentMode,
+
+    /// Picks the adapter used for rendering from the adapters compatible with the window, e.g.,
+    /// by name, vendor or device type. Receives the information about the adapters and returns
+    /// the index of the chosen one. If not set, the adapter is chosen by wgpu based on
+    /// `power_preference`. Not supported on the web, where the default selection is used.
+    /// Defaults to `None`.
+    pub choose_adapter: Option<fn(&[wgpu::AdapterInfo]) -> usize>,
+
+    /// The power preference wgpu chooses the adapter by, e.g., `HighPerformance` for the
+    /// discrete GPU of a laptop instead of the integrated one. Ignored if `choose_adapter` is
+    /// set. Defaults to `PowerPreference::default()`.
+    pub power_preference: wgpu::PowerPreference,
+
+    /// Determines whether the input events received while the canvas is paused are dropped or
+    /// delivered to the handler on resume. See `CanvasControl::pause`. Defaults to
+    /// `PausedInput::Drop`.
+    pub paused_input: PausedInput,
+
+    /// Determines when frames are rendered. Defaults to `RenderMode::Continuous`.
+    pub render_mode: RenderMode,
+
+    /// If set, `EventHandler::update` is called with this fixed time step instead of the frame
+    /// time, zero or more times per frame, e.g., for deterministic physics. The fraction of a
+    /// step the frame lies between the last and the next update is available through
+    /// `RenderContext::interpolation_alpha` for interpolating the rendered state. At most 8
+    /// updates are run per frame to catch up, the remaining time is dropped. Defaults to
+    /// `None`, i.e., `update` is called once per frame with the frame time.
+    pub fixed_timestep: Option<Duration>,
+
+    /// If set, limits the number of frames rendered per second. The event loop waits until the
+    /// frame interval has passed instead of rendering as fast as possible, which saves power,
+    /// e.g., with `wgpu::PresentMode::Immediate`. Defaults to `None`, i.e., no limit.
+    pub max_fps: Option<u32>,
+
+    /// If true, rendering is suspended while the window doesn't have the keyboard focus, like
+    /// while it is minimized, see `EventHandler::on_suspend`. Defaults to false.
+    pub suspend_when_unfocused: bool,
+
+    /// If true, the standard quit shortcuts of the platform, i.e., Cmd+Q on macOS and Alt+F4
+    /// elsewhere, request the canvas to close like the close button of the window, which lets
+    /// the handler veto the request. The shortcut isn't delivered to the input callbacks.
+    /// Defaults to false, e.g., for handlers processing these keys themselves.
+    pub quit_shortcuts: bool,
+
+    /// The maximum interval between two presses of the same mouse button for counting them as
+    /// a double or triple click, see `EventHandler::mouse_button`. Defaults to 500ms.
+    pub double_click_interval: Duration,
+
+    /// The maximum distance in logical pixels the cursor may move between two presses for
+    /// counting them as a double or triple click. Defaults to 4.
+    pub double_click_distance: f64,
+
+    /// The minimum interval between two reconfigurations of the surface while the window is
+    /// being resized, e.g., for avoiding stutter while dragging the border of the window on
+    /// slower GPUs. Resize events within the interval are coalesced and the latest size is
+    /// applied once the interval has passed. Defaults to `None`, i.e., every resize event
+    /// reconfigures the surface immediately.
+    pub resize_throttle: Option<Duration>,
+
+    /// Determines how the canvas recovers from errors while rendering a frame, e.g., whether it
+    /// exits or keeps going if the GPU ran out of memory. Defaults to `DefaultErrorPolicy`.
+    pub error_policy: Box<dyn ErrorPolicy>,
+
+    /// The source of the time returned by `CanvasControl::elapsed` and `CanvasControl::now`,
+    /// e.g., a deterministic clock for tests or a clock synchronized to audio playback.
+    /// Defaults to `MonotonicClock`, i.e., the real time.
+    pub time_source: Box<dyn TimeSource>,
+
+    /// If true, the surface, the device and the setup of the handler are deferred until the
+    /// window is shown for the first time, i.e., until the first resume or redraw request.
+    /// Some platforms fail to create a surface for a window which isn't visible yet. Errors
+    /// during the deferred creation terminate the event loop and are returned by
+    /// `create_and_run_canvas`. Ignored on the web. Defaults to false.
+    pub defer_surface_creation: bool,
+
+    /// The number of worker threads decoding the assets of `CanvasControl::load_asset`.
+    /// Must be at least 1. Defaults to the number of available cores minus one for the event
+    /// loop, but at least 1.
+    #[cfg(feature = "assets")]
+    pub asset_worker_threads: usize,
+
+    /// The resolution the scene is rendered at, decoupled from the resolution of the overlay,
+    /// e.g., for rendering a 3D view at a lower resolution than the UI. Can be changed at
+    /// runtime with `CanvasControl::set_scene_resolution`. Defaults to `None`, i.e., everything
+    /// is rendered at the same resolution.
+    pub scene_resolution: Option<(u32, u32)>,
+
+    /// Records the CPU time of each frame, e.g., for capturing a performance trace of a
+    /// session. The timings are either passed to `EventHandler::frame_timing` or written to a
+    /// CSV file after each frame. Defaults to `FrameProfiling::Disabled`.
+    pub frame_profiling: FrameProfiling,
+
+    /// The number of recent frames the statistics of `RenderContext::frame_stats` are averaged
+    /// over. Defaults to 60.
+    pub frame_stats_window: usize,
+
+    /// If true, the time the GPU spends on the render pass of each frame is measured with
+    /// timestamp queries, passed to `EventHandler::gpu_frame_time` and recorded as
+    /// `FrameTiming::gpu_time` while profiling. The required `wgpu::Features::TIMESTAMP_QUERY`
+    /// is requested automatically, and the measuring is disabled with a warning if the adapter
+    /// doesn't support it. Defaults to false.
+    pub gpu_timing: bool,
+
+    /// The origin of the logical cursor coordinates passed to `EventHandler::cursor_move`,
+    /// `EventHandler::mouse_button` and `EventHandler::touch`, e.g., `CursorOrigin::BottomLeft`
+    /// for code ported from OpenGL. Defaults to `CursorOrigin::TopLeft`, i.e., the convention of
+    /// winit and wgpu.
+    pub cursor_origin: CursorOrigin,
+
+    /// If false, the window is created without decorations, i.e., without title bar and
+    /// borders drawn by the window manager. Can be changed at runtime with
+    /// `CanvasControl::set_decorations`. Defaults to true.
+    pub decorations: bool,
+
+    /// If true, the window is created in borderless fullscreen mode on the current monitor.
+    /// Can be changed at runtime with `CanvasControl::set_fullscreen`. Defaults to false.
+    pub fullscreen: bool,
+
+    /// The icon of the window. It is ignored on macOS, where the icon is taken from the
+    /// application bundle, and on Wayland, where it is taken from the desktop entry matching
+    /// `app_id`. Defaults to `None`, i.e., the platform's default icon.
+    pub icon: Option<WindowIcon>,
+
+    /// The minimum width and height the window can be resized to, interpreted like `width`
+    /// and `height`. Defaults to `None`, i.e., the window can be resized down to zero pixels,
+    /// where rendering is paused.
+    pub min_size: Option<(u32, u32)>,
+
+    /// The maximum width and height the window can be resized to, interpreted like `width`
+    /// and `height`. Defaults to `None`, i.e., no limit.
+    pub max_size: Option<(u32, u32)>,
+
+    /// If false, the window can't be resized by the user. Defaults to true.
+    pub resizable: bool,
+
+    /// If true, the window is created with a transparent background, e.g., for splash screens
+    /// and overlays. The frame is then cleared to transparent black and the surface composites
+    /// its alpha channel with the desktop if the platform supports it. Defaults to false.
+    pub transparent: bool,
+
+    /// The initial position of the top-left corner of the window on the desktop, given in
+    /// physical or logical pixels like `width` and `height`. Defaults to `None`, i.e., the
+    /// window is placed by the platform.
+    pub position: Option<(i32, i32)>,
+}
+
+/// Returns the default DX12 shader compiler depending on the `dxc` feature.
+fn default_dx12_shader_compiler() -> wgpu::Dx12Compiler {
+    if cfg!(feature = "dxc") {
+        wgpu::Dx12Compiler::Dxc {
+            dxil_path: None,
+            dxc_path: None,
+        }
+    } else {
+        wgpu::Dx12Compiler::Fxc
+    }
+}
+
+impl Default for CanvasOptions {
+    fn default() -> Self {
+        Self {
+            width: 800,
+            height: 600,
+            title: "Canvas".to_string(),
+            size_is_physical: false,
+            instance_flags: wgpu::InstanceFlags::from_build_config(),
+            backends: wgpu::Backends::all(),
+            dx12_shader_compiler: default_dx12_shader_compiler(),
+            backend_options: BackendOptions::default(),
+            scaling: ScalingMode::Native,
+            required_downlevel_flags: wgpu::DownlevelFlags::empty(),
+            required_shader_model: wgpu::ShaderModel::Sm2,
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::default(),
+            trace_path: None,
+            app_id: None,
+            max_consecutive_timeouts: 3,
+            preserve_contents: false,
+            fit_to_work_area: false,
+            sample_count: 1,
+            depth: None,
+            clear_stencil: 0,
+            srgb_fallback: SrgbFallback::default(),
+            hdr: false,
+            present_mode: wgpu::PresentMode::Fifo,
+            choose_adapter: None,
+            power_preference: wgpu::PowerPreference::default(),
+            paused_input: PausedInput::default(),
+            render_mode: RenderMode::default(),
+            fixed_timestep: None,
+            max_fps: None,
+            suspend_when_unfocused: false,
+            quit_shortcuts: false,
+            double_click_interval: Duration::from_millis(500),
+            double_click_distance: 4.0,
+            resize_throttle: None,
+            error_policy: Box::new(DefaultErrorPolicy),
+            time_source: Box::new(MonotonicClock::new()),
+            defer_surface_creation: false,
+            #[cfg(feature = "assets")]
+            asset_worker_threads: std::thread::available_parallelism()
+                .map(|n| n.get().saturating_sub(1).max(1))
+                .unwrap_or(1),
+            scene_resolution: None,
+            frame_profiling: FrameProfiling::Disabled,
+            frame_stats_window: 60,
+            gpu_timing: false,
+            cursor_origin: CursorOrigin::TopLeft,
+            decorations: true,
+            fullscreen: false,
+            icon: None,
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            transparent: false,
+            position: None,
+        }
+    }
 }
 
 /// The data for the canvas.
 struct CanvasData<H: EventHandler> {
     surface: wgpu::Surface,
+    adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
-    window: Window,
-    handler: H,
+    window: Rc<Window>,
+    /// The event handler, which is borrowed mutably while the render context borrows the
+    /// rest of the canvas.
+    handler: RefCell<H>,
+    control: CanvasControl,
+    /// Determines how to recover from errors while rendering a frame.
+    error_policy: Box<dyn ErrorPolicy>,
+    /// The offscreen target if the content isn't rendered at the native resolution of the
+    /// surface.
+    scaler: Option<Scaler>,
+    /// The persistent target if the contents are preserved between frames or mirrored in the
+    /// native mode.
+    preserved_target: Option<PreservedTarget>,
+    /// The target the scene is rendered into if its resolution is decoupled from the overlay.
+    scene: Option<SceneTarget>,
+    /// Records the timings of the frames.
+    profiler: FrameProfiler,
+    /// The rolling average of the frame rate.
+    frame_stats: FrameStatsTracker,
+    /// The secondary window showing the same content, if any.
+    mirror: Option<Mirror>,
+    /// The copy of the surface texture for the requested screenshot, which is being mapped.
+    screenshot: Option<TextureReadback>,
+    /// The timestamp queries measuring the GPU time of the frames, if enabled and supported.
+    gpu_timer: Option<GpuTimer>,
+    /// The GPU time read back since the timing of the previous frame has been recorded.
+    gpu_time: Option<Duration>,
+    /// The error the event loop has been exited for by the error policy, if any.
+    fatal_error: Option<Error>,
+    /// True if the contents are preserved between frames.
+    preserve_contents: bool,
+    /// True if the target has to be cleared with the next frame even if contents are preserved.
+    clear_pending: bool,
+    /// The color the frame is cleared to, which is transparent for transparent windows.
+    clear_color: wgpu::Color,
+    /// The number of consecutive timeouts tolerated before an error is reported.
+    max_consecutive_timeouts: u32,
+    /// The number of timeouts that occurred in a row while acquiring the surface texture.
+    consecutive_timeouts: u32,
+    /// The index of the next frame to render.
+    frame_index: u64,
+    /// The number of samples per pixel of the default render pass.
+    sample_count: u32,
+    /// The multisampled color texture the default render pass renders into if the sample
+    /// count is greater than 1, which is created for the frame on demand.
+    msaa_target: Option<Texture>,
+    /// The configuration of the depth buffer, if any.
+    depth: Option<DepthConfig>,
+    /// The depth buffer, which is created for the frame on demand.
+    depth_target: Option<Texture>,
+    /// The value the stencil buffer is cleared to.
+    clear_stencil: u32,
+    /// The time of the time source at the start of the previous frame, if any.
+    last_frame_time: Option<Duration>,
+    /// The time between the start of the previous and the current frame in seconds.
+    delta_time: f32,
+    /// The time step of the updates if they run at a fixed rate.
+    fixed_timestep: Option<Duration>,
+    /// The frame time not yet consumed by fixed updates.
+    update_accumulator: Duration,
+    /// The minimum interval between the start of two frames if the frame rate is limited.
+    frame_interval: Option<Duration>,
+    /// The earliest time the next frame may start if the frame rate is limited.
+    next_frame_start: Option<Instant>,
+    /// The minimum interval between two reconfigurations of the surface, if throttled.
+    resize_throttle: Option<Duration>,
+    /// The time the surface has been reconfigured for a resize the last time.
+    last_resize: Instant,
+    /// The latest size of the window, which hasn't been applied due to throttling.
+    pending_resize: Option<PhysicalSize<u32>>,
+    /// The texts drawn by the handler, which are rendered on top of the frame.
+    #[cfg(feature = "text")]
+    text_overlay: TextOverlay,
+    /// The watcher for recompiling the shaders loaded by the handler when they are modified.
+    #[cfg(feature = "hot-reload")]
+    shader_watcher: ShaderWatcher,
+
+    /// Determines what happens with the input received while paused.
+    paused_input: PausedInput,
+    /// The input events buffered while paused, which are delivered on resume.
+    deferred_input: Vec<InputEvent>,
+
+    /// True if the quit shortcuts of the platform close the canvas.
+    quit_shortcuts: bool,
+    /// The current state of the modifier keys.
+    modifiers: ModifiersState,
+    /// True if the HDR format has been requested for the surface.
+    hdr: bool,
+    /// The behavior if the surface doesn't support any sRGB format.
+    srgb_fallback: SrgbFallback,
+    /// The formats supported by the surface when the format has been selected.
+    surface_formats: Vec<wgpu::TextureFormat>,
+
+    /// The last known cursor position in logical coordinates.
+    cursor_pos: [f64; 2],
+    /// The origin of the cursor coordinates reported to the handler.
+    cursor_origin: CursorOrigin,
+    /// True if the cursor is currently inside the window.
+    cursor_inside: bool,
+    /// True if the window is minimized, i.e., has been resized to zero.
+    minimized: bool,
+    /// True if the window is fully hidden by other windows.
+    occluded: bool,
+    /// True if the window has the keyboard focus.
+    focused: bool,
+    /// True if rendering is suspended while the window doesn't have the focus.
+    suspend_when_unfocused: bool,
+    /// True if rendering is suspended because the window isn't visible.
+    suspended: bool,
+    /// Determines when frames are rendered.
+    render_mode: RenderMode,
+    /// The mouse buttons that are currently held down. A drag is active if this is not empty.
+    pressed_buttons: Vec<MouseButton>,
+    /// The time, the button and the reported cursor position of the last mouse button press.
+    last_click: Option<(Duration, MouseButton, [f64; 2])>,
+    /// The number of presses of the current click sequence, e.g., 2 for a double click.
+    click_count: u32,
+    /// The maximum interval between the presses of a click sequence.
+    double_click_interval: Duration,
+    /// The maximum distance between the presses of a click sequence in logical pixels.
+    double_click_distance: f64,
+    /// The physical keys that are currently held down.
+    pressed_keys: Vec<KeyCode>,
+    /// True if the platform keeps delivering cursor events while the cursor is outside the
+    /// window during the active drag, i.e., the pointer is captured by the window.
+    drag_captured: bool,
 }
 
 impl<H: EventHandler> CanvasData<H> {
@@ -37,16 +624,34 @@ impl<H: EventHandler> CanvasData<H> {
     /// This will also create the respective wgpu objects.
     ///
     /// # Arguments
+    /// * `options` - The options for creating the canvas.
     /// * `window` - The window to create the canvas for.
-    /// * `handler` - The event handler for the canvas.
-    async fn new(window: Window, handler: H) -> Result<Self> {
+    /// * `handler` - The event handler for the canvas, which is only taken if the canvas has
+    ///   been created, so that the caller can still stop it otherwise.
+    /// * `error_policy` - The policy for recovering from errors while rendering a frame.
+    /// * `time_source` - The source of the time the canvas is driven by.
+    /// * `controller` - The controller for accessing the canvas from other threads.
+    async fn new(
+        options: &CanvasOptions,
+        window: Rc<Window>,
+        handler: &mut Option<H>,
+        error_policy: Box<dyn ErrorPolicy>,
+        time_source: Box<dyn TimeSource>,
+        controller: CanvasController,
+    ) -> Result<Self> {
+        check_clear_values(options)?;
+        Self::check_frame_timing(options)?;
+        check_scene_resolution(options.scene_resolution)?;
+
         let size = window.inner_size();
 
         // The instance is a handle to our GPU
         // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
+            backends: options.backends,
+            flags: options.instance_flags,
+            dx12_shader_compiler: options.dx12_shader_compiler.clone(),
+            gles_minor_version: options.backend_options.gles_minor_version,
         });
 
         // # Safety
@@ -54,73 +659,350 @@ impl<H: EventHandler> CanvasData<H> {
         // The surface needs to live as long as the window that created it.
         // State owns the window, so this should be safe.
         debug!("Create surface...");
-        let surface = unsafe { instance.create_surface(&window) }
+        let surface = unsafe { instance.create_surface(window.as_ref()) }
             .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
 
         debug!("Choose adapter...");
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .ok_or_else(|| Error::GraphicsAPI("No suitable adapter found".to_string()))?;
+        let chosen_adapter = match options.choose_adapter {
+            Some(choose) => choose_adapter(choose, &instance, Some(&surface))?,
+            None => None,
+        };
+        let adapter = match chosen_adapter {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: Some(&surface),
+                    force_fallback_adapter: false,
+                })
+                .await
+                .ok_or_else(|| Error::GraphicsAPI("No suitable adapter found".to_string()))?,
+        };
 
         // print some infos about the adapter
-        {
-            let adapter_info = adapter.get_info();
-            info!("Adapter name: {}", adapter_info.name);
-            info!("Adapter vendor: {}", adapter_info.vendor);
-            info!("Adapter driver: {}", adapter_info.driver);
-            info!("Adapter driver info: {}", adapter_info.driver_info);
-            info!("Adapter backend API: {}", adapter_info.backend.to_str());
-        }
+        let adapter_info = adapter.get_info();
+        info!("Adapter name: {}", adapter_info.name);
+        info!("Adapter vendor: {}", adapter_info.vendor);
+        info!("Adapter driver: {}", adapter_info.driver);
+        info!("Adapter driver info: {}", adapter_info.driver_info);
+        info!("Adapter backend API: {}", adapter_info.backend.to_str());
+
+        check_downlevel_capabilities(options, &adapter)?;
 
         // create the device and command queue
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::empty(),
-                    limits: wgpu::Limits::default(),
-                    label: None,
-                },
-                None, // Trace path
-            )
-            .await
-            .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+        let (device, queue) = request_device(options, &adapter).await?;
+        let gpu_timer = options.gpu_timing.then(|| {
+            if device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+                Some(GpuTimer::new(&device, &queue))
+            } else {
+                warn!("Adapter doesn't support timestamp queries, GPU timing is disabled");
+                None
+            }
+        });
 
         let surface_caps = surface.get_capabilities(&adapter);
-        // Shader code in this program assumes an sRGB surface texture. Using a different
-        // one will result in all the colors coming out darker. If you want to support non
-        // sRGB surfaces, you'll need to account for that when drawing to the frame.
-        let surface_format = surface_caps
-            .formats
-            .iter()
-            .copied()
-            .filter(|f| f.is_srgb())
-            .next()
-            .unwrap_or(surface_caps.formats[0]);
+        let surface_format =
+            Self::select_surface_format(options.hdr, options.srgb_fallback, &surface_caps.formats)?;
+        // the surface texture is copied for screenshots if the surface supports it
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
         let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: surface_caps.present_modes[0],
-            alpha_mode: surface_caps.alpha_modes[0],
+            present_mode: Self::select_present_mode(
+                options.present_mode,
+                &surface_caps.present_modes,
+            )?,
+            alpha_mode: Self::select_alpha_mode(options.transparent, &surface_caps.alpha_modes),
             view_formats: vec![],
         };
         surface.configure(&device, &config);
+        info!(
+            "Surface format: {:?}, present mode: {:?}, alpha mode: {:?}",
+            config.format, config.present_mode, config.alpha_mode
+        );
+
+        match options.scaling {
+            ScalingMode::Native => {}
+            ScalingMode::IntegerScaled { width, height } => {
+                if width == 0 || height == 0 {
+                    return Err(Error::Internal(format!(
+                        "Invalid integer scaled resolution {}x{}",
+                        width, height
+                    )));
+                }
+            }
+            ScalingMode::Letterboxed {
+                aspect_width,
+                aspect_height,
+            } => {
+                if aspect_width == 0 || aspect_height == 0 {
+                    return Err(Error::Internal(format!(
+                        "Invalid letterboxed aspect ratio {}:{}",
+                        aspect_width, aspect_height
+                    )));
+                }
+            }
+        }
+
+        let scaler = Scaler::new(
+            &device,
+            surface_format,
+            options.scaling,
+            (size.width, size.height),
+        );
+
+        let preserved_target = match (&scaler, options.preserve_contents) {
+            (None, true) => Some(PreservedTarget::new(
+                &device,
+                surface_format,
+                (size.width, size.height),
+            )),
+            _ => None,
+        };
+
+        let profiler = FrameProfiler::new(options.frame_profiling.clone())?;
+        let frame_stats = FrameStatsTracker::new(options.frame_stats_window)?;
+
+        let scene = options
+            .scene_resolution
+            .map(|resolution| SceneTarget::new(&device, surface_format, resolution));
+
+        #[cfg(feature = "text")]
+        let text_overlay = TextOverlay::new(&device, &queue, surface_format);
+
+        let sample_count = select_sample_count(
+            options.sample_count,
+            &adapter,
+            &device,
+            surface_format,
+            options.depth.map(|depth| depth.format),
+        )?;
 
-        Ok(Self {
+        let control = CanvasControl::new(
+            Some(window.clone()),
+            instance,
+            adapter_info,
+            config.clone(),
+            options,
+            time_source,
+            controller,
+        )?;
+        control.set_sample_count(sample_count);
+
+        let Some(handler) = handler.take() else {
+            return Err(Error::Internal(
+                "The handler has already been handed over".to_string(),
+            ));
+        };
+
+        let canvas = Self {
             window,
             surface,
+            adapter,
             device,
             queue,
             config,
             size,
-            handler,
-        })
+            handler: RefCell::new(handler),
+            control,
+            error_policy,
+            scaler,
+            preserved_target,
+            scene,
+            profiler,
+            frame_stats,
+            mirror: None,
+            screenshot: None,
+            gpu_timer: gpu_timer.flatten(),
+            gpu_time: None,
+            fatal_error: None,
+            preserve_contents: options.preserve_contents,
+            clear_pending: true,
+            clear_color: if options.transparent {
+                wgpu::Color::TRANSPARENT
+            } else {
+                CLEAR_COLOR
+            },
+            max_consecutive_timeouts: options.max_consecutive_timeouts,
+            consecutive_timeouts: 0,
+            frame_index: 0,
+            sample_count,
+            msaa_target: None,
+            depth: options.depth,
+            depth_target: None,
+            clear_stencil: options.clear_stencil,
+            last_frame_time: None,
+            delta_time: 0.0,
+            fixed_timestep: options.fixed_timestep,
+            update_accumulator: Duration::ZERO,
+            frame_interval: options
+                .max_fps
+                .map(|max_fps| Duration::from_secs_f64(1.0 / max_fps as f64)),
+            next_frame_start: None,
+            resize_throttle: options.resize_throttle,
+            last_resize: Instant::now(),
+            pending_resize: None,
+            #[cfg(feature = "text")]
+            text_overlay,
+            #[cfg(feature = "hot-reload")]
+            shader_watcher: ShaderWatcher::new(),
+            paused_input: options.paused_input,
+            deferred_input: Vec::new(),
+            quit_shortcuts: options.quit_shortcuts,
+            modifiers: ModifiersState::empty(),
+            hdr: options.hdr,
+            srgb_fallback: options.srgb_fallback,
+            surface_formats: surface_caps.formats,
+            cursor_pos: [0.0, 0.0],
+            cursor_origin: options.cursor_origin,
+            cursor_inside: false,
+            minimized: false,
+            occluded: false,
+            focused: true,
+            suspend_when_unfocused: options.suspend_when_unfocused,
+            suspended: false,
+            render_mode: options.render_mode,
+            pressed_buttons: Vec::new(),
+            last_click: None,
+            click_count: 0,
+            double_click_interval: options.double_click_interval,
+            double_click_distance: options.double_click_distance,
+            pressed_keys: Vec::new(),
+            drag_captured: false,
+        };
+        canvas.update_content_viewport();
+
+        Ok(canvas)
+    }
+
+    /// Selects the present mode of the surface, which is the requested one if it is supported
+    /// and the first supported one otherwise.
+    ///
+    /// # Arguments
+    /// * `requested` - The present mode requested in the options.
+    /// * `present_modes` - The present modes supported by the surface.
+    fn select_present_mode(
+        requested: wgpu::PresentMode,
+        present_modes: &[wgpu::PresentMode],
+    ) -> Result<wgpu::PresentMode> {
+        if present_modes.contains(&requested) {
+            return Ok(requested);
+        }
+
+        let first = *present_modes.first().ok_or_else(|| {
+            Error::GraphicsAPI("Surface doesn't support any present mode".to_string())
+        })?;
+
+        warn!(
+            "Surface doesn't support the present mode {:?}, falling back to {:?}",
+            requested, first
+        );
+
+        Ok(first)
+    }
+
+    /// Selects the alpha mode of the surface, which is the first supported one for opaque
+    /// windows. Transparent windows need an alpha mode blending the frame with the desktop,
+    /// i.e., pre-multiplied or post-multiplied alpha.
+    ///
+    /// # Arguments
+    /// * `transparent` - True if the window is transparent.
+    /// * `alpha_modes` - The alpha modes supported by the surface.
+    fn select_alpha_mode(
+        transparent: bool,
+        alpha_modes: &[wgpu::CompositeAlphaMode],
+    ) -> wgpu::CompositeAlphaMode {
+        let first = alpha_modes[0];
+        if !transparent {
+            return first;
+        }
+
+        let blending = [
+            wgpu::CompositeAlphaMode::PreMultiplied,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+        ];
+        match blending.into_iter().find(|mode| alpha_modes.contains(mode)) {
+            Some(mode) => mode,
+            None => {
+                warn!(
+                    "Surface doesn't support a blending alpha mode, falling back to {:?}",
+                    first
+                );
+                first
+            }
+        }
+    }
+
+    /// Selects the format of the surface from the supported formats.
+    /// Shader code assumes an sRGB surface texture. Using a different one will result in all
+    /// the colors coming out darker, so the behavior in this case is determined by the options.
+    /// The HDR format is preferred if it has been requested and is supported.
+    ///
+    /// # Arguments
+    /// * `hdr` - True if the HDR format has been requested.
+    /// * `srgb_fallback` - The behavior if the surface doesn't support any sRGB format.
+    /// * `formats` - The formats supported by the surface, the preferred one first.
+    fn select_surface_format(
+        hdr: bool,
+        srgb_fallback: SrgbFallback,
+        formats: &[wgpu::TextureFormat],
+    ) -> Result<wgpu::TextureFormat> {
+        let first = *formats
+            .first()
+            .ok_or_else(|| Error::GraphicsAPI("Surface doesn't support any format".to_string()))?;
+
+        if hdr {
+            if formats.contains(&HDR_FORMAT) {
+                return Ok(HDR_FORMAT);
+            }
+
+            warn!(
+                "Surface doesn't support the HDR format {:?}, falling back to SDR",
+                HDR_FORMAT
+            );
+        }
+
+        if srgb_fallback == SrgbFallback::ForceFirst {
+            return Ok(first);
+        }
+
+        if let Some(format) = formats.iter().copied().find(|f| f.is_srgb()) {
+            return Ok(format);
+        }
+
+        match srgb_fallback {
+            SrgbFallback::Error => Err(Error::GraphicsAPI(format!(
+                "Surface doesn't support any sRGB format, supported formats are {:?}",
+                formats
+            ))),
+            _ => {
+                warn!(
+                    "Surface doesn't support any sRGB format, falling back to {:?}. Colors \
+                     will appear darker unless the handler converts them to sRGB itself.",
+                    first
+                );
+                Ok(first)
+            }
+        }
+    }
+
+    /// Checks that the fixed time step and the frame rate limit are valid.
+    ///
+    /// # Arguments
+    /// * `options` - The options with the frame timing.
+    fn check_frame_timing(options: &CanvasOptions) -> Result<()> {
+        if options.fixed_timestep == Some(Duration::ZERO) {
+            return Err(Error::Internal("Invalid fixed timestep 0".to_string()));
+        }
+
+        if options.max_fps == Some(0) {
+            return Err(Error::Internal("Invalid frame rate limit 0".to_string()));
+        }
+
+        Ok(())
     }
 
     pub fn window(&self) -> &Window {
@@ -128,169 +1010,2353 @@ impl<H: EventHandler> CanvasData<H> {
     }
 
     pub fn handler(&mut self) -> &mut H {
-        &mut self.handler
+        self.handler.get_mut()
     }
 
-    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
-        if new_size.width > 0 && new_size.height > 0 {
-            self.size = new_size;
-            self.config.width = new_size.width;
-            self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+    /// Returns the size of the buffer the handler renders into, i.e., the actual size of the
+    /// surface in physical pixels, the fixed resolution in the integer scaled mode or the size
+    /// of the letterboxed rectangle.
+    fn target_size(&self) -> (u32, u32) {
+        match &self.scaler {
+            Some(scaler) => scaler.size(),
+            None => (self.size.width, self.size.height),
+        }
+    }
+
+    /// Returns the context passed to the callbacks of the handler.
+    fn render_context(&self) -> RenderContext<'_> {
+        let ctx = RenderContext::new(
+            &self.device,
+            &self.queue,
+            &self.control,
+            &self.config,
+            self.target_size(),
+            self.frame_index,
+        )
+        .with_scene_size(self.scene.as_ref().map(|scene| scene.size()))
+        .with_delta_time(self.delta_time)
+        .with_interpolation_alpha(self.interpolation_alpha())
+        .with_frame_stats(self.frame_stats.stats(self.frame_index))
+        .with_depth_view(self.depth_target.as_ref().map(|depth| &depth.view));
+        #[cfg(feature = "text")]
+        let ctx = ctx.with_text_overlay(&self.text_overlay);
+        #[cfg(feature = "hot-reload")]
+        let ctx = ctx.with_shader_watcher(&self.shader_watcher);
+
+        ctx
+    }
 
-            self.handler.resize(new_size.width, new_size.height);
+    /// Returns the size of the texture the frame is rendered into, which is the scene target if
+    /// its resolution is decoupled and the buffer of the handler otherwise.
+    fn frame_size(&self) -> (u32, u32) {
+        match &self.scene {
+            Some(scene) => scene.size(),
+            None => self.target_size(),
         }
     }
 
-    /// Returns true if the event should be captured.
+    /// Creates or recreates the depth buffer and the multisampled color texture if they don't
+    /// match the frame anymore.
+    fn update_frame_attachments(&mut self) {
+        let size = self.frame_size();
+
+        if let Some(depth) = self.depth {
+            self.depth_target = Some(update_attachment(
+                &self.device,
+                self.depth_target.take(),
+                "Depth Buffer",
+                size,
+                depth.format,
+                self.sample_count,
+            ));
+        }
+
+        if self.sample_count > 1 {
+            self.msaa_target = Some(update_attachment(
+                &self.device,
+                self.msaa_target.take(),
+                "Multisampled Frame",
+                size,
+                self.config.format,
+                self.sample_count,
+            ));
+        }
+    }
+
+    /// Records the copy of the surface texture if a screenshot has been requested. The request
+    /// stays pending while the previous screenshot is still being mapped.
     ///
     /// # Arguments
-    /// * `event` - The event to check.
-    fn input(&mut self, event: &WindowEvent) -> bool {
-        false
+    /// * `encoder` - The encoder of the frame.
+    /// * `texture` - The surface texture with the final content of the frame.
+    fn record_screenshot(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Option<Result<TextureReadback>> {
+        if self.screenshot.is_some() || !self.control.take_screenshot_request() {
+            return None;
+        }
+
+        if !self.config.usage.contains(wgpu::TextureUsages::COPY_SRC) {
+            return Some(Err(Error::GraphicsAPI(
+                "The surface doesn't support copying its texture for screenshots".to_string(),
+            )));
+        }
+
+        Some(TextureReadback::new(&self.device, encoder, texture))
     }
 
-    fn update(&mut self) {}
+    /// Delivers the screenshot to the handler once its buffer has been mapped.
+    fn poll_screenshot(&mut self) {
+        let readback = match &self.screenshot {
+            Some(readback) => readback,
+            None => return,
+        };
 
-    fn render(&mut self) -> Result<()> {
-        let output = self.surface.get_current_texture()?;
+        self.device.poll(wgpu::Maintain::Poll);
+        let result = match readback.try_read() {
+            Some(result) => result,
+            None => return,
+        };
 
-        let view = output
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let (width, height) = readback.size();
+        let format = readback.format();
+        self.screenshot = None;
 
-        let mut encoder = self
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Render Encoder"),
-            });
+        match result.and_then(|pixels| to_rgba(format, pixels)) {
+            Ok(rgba) => self.handler.get_mut().screenshot_ready(width, height, rgba),
+            Err(e) => {
+                error!("{}", e);
+                self.handler.get_mut().render_error(&e);
+            }
+        }
+    }
 
-        {
-            let _render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.1,
-                            g: 0.2,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-            });
+    /// Passes the GPU time of the last measured frame to the handler once it has been read back.
+    fn poll_gpu_timer(&mut self) {
+        let timer = match &mut self.gpu_timer {
+            Some(timer) => timer,
+            None => return,
+        };
+
+        self.device.poll(wgpu::Maintain::Poll);
+        match timer.try_read() {
+            Some(Ok(gpu_time)) => {
+                self.gpu_time = Some(gpu_time);
+                self.handler
+                    .get_mut()
+                    .gpu_frame_time(gpu_time.as_secs_f64() * 1_000_000.0);
+            }
+            Some(Err(e)) => {
+                error!("{}", e);
+                self.handler.get_mut().render_error(&e);
+            }
+            None => {}
         }
+    }
 
-        // submit will accept anything that implements IntoIter
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+    /// Publishes the rectangle of the surface the content is shown in to the control handle.
+    fn update_content_viewport(&self) {
+        let viewport = match &self.scaler {
+            Some(scaler) => scaler.viewport(),
+            None => Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: self.size.width as f32,
+                height: self.size.height as f32,
+            },
+        };
+
+        self.control.set_content_viewport(viewport);
+    }
 
-        self.handler.next_frame();
+    /// Calls the setup of the handler with the size of the buffer the handler renders into.
+    fn setup_handler(&mut self) -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let (width, height) = self.target_size();
+        let ctx = self.render_context();
 
-        Ok(())
+        self.handler.borrow_mut().setup(&ctx, width, height)
     }
-}
 
-pub async fn create_and_run_canvas<H>(options: CanvasOptions, mut handler: H) -> Result<()>
-where
-    H: EventHandler,
-{
-    info!("Creating canvas...");
+    /// Handles a resize event of the window, which is applied immediately or deferred until
+    /// the throttle interval has passed.
+    ///
+    /// # Arguments
+    /// * `new_size` - The new size of the window in physical pixels.
+    fn resized(&mut self, new_size: PhysicalSize<u32>) {
+        if let Some(throttle) = self.resize_throttle {
+            if self.last_resize.elapsed() < throttle {
+                self.pending_resize = Some(new_size);
+                return;
+            }
+        }
 
-    // create event loop with control flow set to Poll, i.e., the event loop will run as fast as
-    // possible
-    debug!("Create event loop...");
-    let event_loop = EventLoop::new().map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
-    event_loop.set_control_flow(ControlFlow::Poll);
+        self.resize(new_size);
+    }
 
-    debug!("Create window...");
-    let window = WindowBuilder::new()
-        .with_title(options.title)
-        .with_inner_size(LogicalSize::new(options.width, options.height))
-        .build(&event_loop)
-        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+    /// Applies the deferred resize once the throttle interval has passed.
+    ///
+    /// # Arguments
+    /// * `force` - If true, the resize is applied even if the interval hasn't passed yet.
+    fn apply_pending_resize(&mut self, force: bool) {
+        let interval_passed = self
+            .resize_throttle
+            .is_none_or(|throttle| self.last_resize.elapsed() >= throttle);
 
-    let mut canvas_data = CanvasData::new(window, handler).await?;
-    if let Err(err) = canvas_data.handler().setup(options.width, options.height) {
-        error!("Error during setup: {}", err);
-        return Err(Error::Internal(format!("Error during setup: {}", err)));
-    }
-
-    let mut cursor_pos = [0.0, 0.0];
-
-    event_loop
-        .run(move |event, window_target| {
-            let scale_factor = canvas_data.window().scale_factor();
-
-            match event {
-                Event::WindowEvent { event, window_id }
-                    if window_id == canvas_data.window().id() =>
-                {
-                    if !canvas_data.input(&event) {
-                        match event {
-                            WindowEvent::Resized(size) => {
-                                canvas_data.resize(size);
-                            }
-                            WindowEvent::CursorMoved { position, .. } => {
-                                let logical_position =
-                                    LogicalPosition::from_physical(position, scale_factor);
-
-                                cursor_pos = [logical_position.x, logical_position.y];
-                                canvas_data
-                                    .handler()
-                                    .cursor_move(logical_position.x, logical_position.y);
-                            }
-                            WindowEvent::MouseInput { state, button, .. } => {
-                                let x = cursor_pos[0];
-                                let y = cursor_pos[1];
-
-                                let pressed: bool = state == ElementState::Pressed;
-
-                                canvas_data.handler().mouse_button(x, y, button, pressed);
-                            }
-                            WindowEvent::KeyboardInput { event, .. } => {
-                                let pressed = event.state == ElementState::Pressed;
-                                canvas_data
-                                    .handler()
-                                    .keyboard_event(event.logical_key, pressed);
-                            }
-                            WindowEvent::CloseRequested => window_target.exit(),
-                            WindowEvent::RedrawRequested => {
-                                canvas_data.update();
-                                match canvas_data.render() {
-                                    Ok(_) => {}
-                                    // Reconfigure the surface if lost
-                                    Err(Error::ContextLost(_)) => {
-                                        canvas_data.resize(canvas_data.size)
-                                    }
-                                    // The system is out of memory, we should probably quit
-                                    Err(Error::OutOfMemory(_)) => {
-                                        error!("Out of memory");
-                                        window_target.exit();
-                                    }
-                                    // All other errors (Outdated, Timeout) should be resolved by the next frame
-                                    Err(e) => {
-                                        error!("{:?}", e)
-                                    }
-                                }
-                            }
-                            _ => (),
-                        }
-                    }
-                }
-                Event::AboutToWait => {
-                    canvas_data.window().request_redraw();
-                }
-                _ => (),
+        if force || interval_passed {
+            if let Some(size) = self.pending_resize {
+                self.resize(size);
             }
-        })
-        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+        }
+    }
 
-    Ok(())
+    /// Creates, recreates or drops the scene target to match the resolution requested by the
+    /// handler.
+    fn update_scene_target(&mut self) {
+        let resolution = self.control.scene_resolution();
+        if resolution == self.scene.as_ref().map(|scene| scene.size()) {
+            return;
+        }
+
+        self.scene = resolution
+            .map(|resolution| SceneTarget::new(&self.device, self.config.format, resolution));
+        self.clear_pending = true;
+    }
+
+    /// Configures the surface with the current configuration and publishes it to the control.
+    fn configure_surface(&self) {
+        self.surface.configure(&self.device, &self.config);
+        self.control.set_surface_config(&self.config);
+    }
+
+    /// Applies the surface configuration requested by the handler, if any. Rejected
+    /// configurations are reported to the handler.
+    fn apply_requested_surface_config(&mut self) {
+        if let Some(config) = self.control.take_requested_surface_config() {
+            if let Err(e) = self.apply_surface_config(config) {
+                error!("{}", e);
+                self.handler.get_mut().render_error(&e);
+            }
+        }
+    }
+
+    /// Validates the given configuration against the capabilities of the surface and
+    /// reconfigures the surface with it. The targets the handler renders into are recreated if
+    /// the format changes.
+    ///
+    /// # Arguments
+    /// * `config` - The new configuration of the surface.
+    fn apply_surface_config(&mut self, mut config: wgpu::SurfaceConfiguration) -> Result<()> {
+        let caps = self.surface.get_capabilities(&self.adapter);
+
+        if !caps.formats.contains(&config.format) {
+            return Err(Error::Internal(format!(
+                "Surface format {:?} isn't supported, supported formats are {:?}",
+                config.format, caps.formats
+            )));
+        }
+
+        if !caps.present_modes.contains(&config.present_mode) {
+            return Err(Error::Internal(format!(
+                "Present mode {:?} isn't supported, supported modes are {:?}",
+                config.present_mode, caps.present_modes
+            )));
+        }
+
+        if !caps.alpha_modes.contains(&config.alpha_mode) {
+            return Err(Error::Internal(format!(
+                "Alpha mode {:?} isn't supported, supported modes are {:?}",
+                config.alpha_mode, caps.alpha_modes
+            )));
+        }
+
+        if !config
+            .usage
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+            || !caps.usages.contains(config.usage)
+        {
+            return Err(Error::Internal(format!(
+                "Surface usage {:?} isn't supported, it must contain RENDER_ATTACHMENT and be \
+                 within {:?}",
+                config.usage, caps.usages
+            )));
+        }
+
+        if let Some(view_format) = config
+            .view_formats
+            .iter()
+            .find(|f| f.remove_srgb_suffix() != config.format.remove_srgb_suffix())
+        {
+            return Err(Error::Internal(format!(
+                "View format {:?} is incompatible with the surface format {:?}",
+                view_format, config.format
+            )));
+        }
+
+        // the size is determined by the window
+        config.width = self.config.width;
+        config.height = self.config.height;
+
+        let format_changed = config.format != self.config.format;
+        self.config = config;
+        self.configure_surface();
+
+        if format_changed {
+            self.recreate_targets();
+            self.notify_surface_format_changed();
+        }
+
+        Ok(())
+    }
+
+    /// Selects the format of the surface again if the formats supported by the surface have
+    /// changed since the last selection, e.g., because HDR has been toggled by the OS.
+    /// Returns true if the format of the configuration has changed.
+    fn update_surface_format(&mut self) -> bool {
+        let formats = self.surface.get_capabilities(&self.adapter).formats;
+        if formats.is_empty() || formats == self.surface_formats {
+            return false;
+        }
+
+        let format = match Self::select_surface_format(self.hdr, self.srgb_fallback, &formats) {
+            Ok(format) => format,
+            Err(e) => {
+                warn!("Keeping surface format {:?}: {}", self.config.format, e);
+                return false;
+            }
+        };
+        self.surface_formats = formats;
+
+        if format == self.config.format {
+            return false;
+        }
+
+        info!(
+            "Preferred surface format changed from {:?} to {:?}",
+            self.config.format, format
+        );
+        self.config.format = format;
+        self.config
+            .view_formats
+            .retain(|f| f.remove_srgb_suffix() == format.remove_srgb_suffix());
+
+        true
+    }
+
+    /// Hands a decoded asset to the handler.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the asset.
+    /// * `asset` - The decoded asset or the error returned by the decoder.
+    #[cfg(feature = "assets")]
+    fn asset_loaded(&mut self, id: AssetId, asset: Result<Asset>) {
+        let ctx = self.render_context();
+        self.handler.borrow_mut().asset_loaded(&ctx, id, asset);
+    }
+
+    /// Notifies the handler that the format of the surface has changed.
+    fn notify_surface_format_changed(&mut self) {
+        let ctx = self.render_context();
+        self.handler
+            .borrow_mut()
+            .surface_format_changed(&ctx, self.config.format);
+    }
+
+    /// Recreates the device, the surface and all resources created with the device, e.g., after
+    /// the device has been lost, and lets the handler recreate its resources. The device is
+    /// requested from the same adapter with the features and limits of the previous one.
+    fn recover(&mut self) -> Result<()> {
+        if cfg!(target_arch = "wasm32") {
+            return Err(Error::GraphicsAPI(
+                "Recovering the device isn't supported on the web".to_string(),
+            ));
+        }
+
+        info!("Recreating the device and the surface...");
+
+        // # Safety
+        //
+        // The surface needs to live as long as the window that created it.
+        // State owns the window, so this should be safe.
+        let surface = unsafe { self.control.instance().create_surface(self.window.as_ref()) }
+            .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+        let (device, queue) = pollster::block_on(self.adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                features: self.device.features(),
+                limits: self.device.limits(),
+                label: None,
+            },
+            None,
+        ))
+        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+
+        self.surface = surface;
+        self.device = device;
+        self.queue = queue;
+        self.configure_surface();
+
+        // the mirror window is reopened with a surface for the new device
+        let mirror_title = self.mirror.as_ref().map(|mirror| mirror.window().title());
+        self.close_mirror();
+        if let Some(title) = mirror_title {
+            self.control.open_mirror(&title);
+        }
+
+        self.gpu_timer = self
+            .gpu_timer
+            .is_some()
+            .then(|| GpuTimer::new(&self.device, &self.queue));
+        self.gpu_time = None;
+        self.screenshot = None;
+        self.msaa_target = None;
+        self.depth_target = None;
+        #[cfg(feature = "hot-reload")]
+        {
+            self.shader_watcher = ShaderWatcher::new();
+        }
+        self.recreate_targets();
+
+        let (width, height) = self.target_size();
+        let ctx = self.render_context();
+        self.handler
+            .borrow_mut()
+            .device_recovered(&ctx, width, height)
+            .map_err(|e| Error::Internal(format!("Error during recovery: {}", e)))
+    }
+
+    /// Recreates the offscreen targets with the current format of the surface, which discards
+    /// their contents.
+    fn recreate_targets(&mut self) {
+        let format = self.config.format;
+        let size = (self.size.width, self.size.height);
+
+        if let Some(scaler) = &self.scaler {
+            self.scaler = Scaler::new(&self.device, format, scaler.mode(), size);
+        }
+
+        if self.preserved_target.is_some() {
+            self.preserved_target = Some(PreservedTarget::new(&self.device, format, size));
+        }
+
+        if let Some(scene) = &self.scene {
+            self.scene = Some(SceneTarget::new(&self.device, format, scene.size()));
+        }
+
+        #[cfg(feature = "text")]
+        {
+            self.text_overlay = TextOverlay::new(&self.device, &self.queue, format);
+        }
+
+        self.clear_pending = true;
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.pending_resize = None;
+        self.last_resize = Instant::now();
+
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+
+            let format_changed = self.update_surface_format();
+            self.configure_surface();
+            if format_changed {
+                self.recreate_targets();
+                self.notify_surface_format_changed();
+            }
+
+            if let Some(target) = &mut self.preserved_target {
+                if target.resize(&self.device, (new_size.width, new_size.height)) {
+                    self.clear_pending = true;
+                }
+            }
+
+            // the buffer of the handler only changes if it isn't fixed by the scaling mode
+            match &mut self.scaler {
+                Some(scaler) => {
+                    if scaler.resize(&self.device, (new_size.width, new_size.height)) {
+                        self.clear_pending = true;
+
+                        let (width, height) = scaler.size();
+                        self.handler.get_mut().resize(width, height);
+                    }
+                }
+                None => self
+                    .handler
+                    .get_mut()
+                    .resize(new_size.width, new_size.height),
+            }
+
+            self.update_content_viewport();
+            self.control.request_redraw();
+        }
+    }
+
+    /// Suspends rendering while the window is minimized, occluded or optionally unfocused and
+    /// resumes it once the window is visible again. While suspended, the event loop waits for
+    /// events instead of polling, so the CPU idles.
+    ///
+    /// # Arguments
+    /// * `window_target` - The window target of the event loop for changing the control flow.
+    fn update_suspension(&mut self, window_target: &EventLoopWindowTarget<CanvasEvent>) {
+        let suspended =
+            self.minimized || self.occluded || (self.suspend_when_unfocused && !self.focused);
+        if suspended == self.suspended {
+            return;
+        }
+
+        self.suspended = suspended;
+        if suspended {
+            info!("Window isn't visible, suspending rendering");
+            window_target.set_control_flow(ControlFlow::Wait);
+            self.handler.get_mut().on_suspend();
+        } else {
+            info!("Window is visible again, resuming rendering");
+            window_target.set_control_flow(self.render_mode.control_flow());
+
+            // the surface may have become outdated while the window was hidden
+            self.configure_surface();
+            self.handler.get_mut().on_resume();
+        }
+    }
+
+    /// Requests the next frame if it should be rendered, or lets the event loop wait until the
+    /// frame interval has passed if the frame rate is limited.
+    ///
+    /// # Arguments
+    /// * `window_target` - The window target of the event loop for setting the control flow.
+    fn schedule_frame(&mut self, window_target: &EventLoopWindowTarget<CanvasEvent>) {
+        if let Some(next_frame_start) = self.next_frame_start {
+            if Instant::now() < next_frame_start {
+                window_target.set_control_flow(ControlFlow::WaitUntil(next_frame_start));
+                return;
+            }
+        }
+
+        window_target.set_control_flow(self.render_mode.control_flow());
+        if self.should_redraw() {
+            self.window().request_redraw();
+        }
+    }
+
+    /// Returns true if the next frame should be rendered, i.e., always in the continuous mode
+    /// and only if a redraw has been requested in the on-demand mode.
+    fn should_redraw(&self) -> bool {
+        match self.render_mode {
+            RenderMode::Continuous => true,
+            RenderMode::OnDemand => self.control.take_redraw_request(),
+        }
+    }
+
+    /// Delivers the input buffered while paused once the canvas has been resumed.
+    fn deliver_deferred_input(&mut self) {
+        if !self.control.is_paused() && !self.deferred_input.is_empty() {
+            let events = std::mem::take(&mut self.deferred_input);
+            self.handler.get_mut().deferred_input(events);
+        }
+    }
+
+    /// Opens or closes the mirror window as requested by the handler.
+    ///
+    /// # Arguments
+    /// * `window_target` - The window target of the event loop for creating the window.
+    fn update_mirror(&mut self, window_target: &EventLoopWindowTarget<CanvasEvent>) {
+        match self.control.take_mirror_request() {
+            Some(MirrorRequest::Open(title)) => {
+                if let Some(mirror) = &self.mirror {
+                    mirror.window().set_title(&title);
+                    return;
+                }
+
+                if let Err(e) = self.open_mirror(&title, window_target) {
+                    error!("Failed to open mirror window: {}", e);
+                    self.handler.get_mut().render_error(&e);
+                }
+            }
+            Some(MirrorRequest::Close) => self.close_mirror(),
+            None => {}
+        }
+    }
+
+    /// Opens the mirror window and makes sure the content is rendered into an offscreen target,
+    /// which can be drawn onto both surfaces.
+    ///
+    /// # Arguments
+    /// * `title` - The title of the mirror window.
+    /// * `window_target` - The window target of the event loop for creating the window.
+    fn open_mirror(
+        &mut self,
+        title: &str,
+        window_target: &EventLoopWindowTarget<CanvasEvent>,
+    ) -> Result<()> {
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .with_inner_size(self.size)
+            .build(window_target)
+            .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+
+        let mirror = Mirror::new(
+            self.control.instance(),
+            &self.adapter,
+            &self.device,
+            window,
+            self.config.format,
+        )?;
+
+        if self.scaler.is_none() && self.preserved_target.is_none() {
+            self.preserved_target = Some(PreservedTarget::new(
+                &self.device,
+                self.config.format,
+                (self.size.width, self.size.height),
+            ));
+        }
+
+        self.mirror = Some(mirror);
+        self.control.set_mirror_open(true);
+
+        Ok(())
+    }
+
+    /// Closes the mirror window and drops the offscreen target if it was only needed for
+    /// mirroring.
+    fn close_mirror(&mut self) {
+        if self.mirror.take().is_some() && !self.preserve_contents {
+            self.preserved_target = None;
+        }
+
+        self.control.set_mirror_open(false);
+    }
+
+    /// Returns true if the given window is the mirror window.
+    ///
+    /// # Arguments
+    /// * `window_id` - The id of the window to check.
+    fn is_mirror(&self, window_id: WindowId) -> bool {
+        self.mirror
+            .as_ref()
+            .is_some_and(|mirror| mirror.id() == window_id)
+    }
+
+    /// Handles the events of the mirror window. Input is ignored.
+    ///
+    /// # Arguments
+    /// * `event` - The event of the mirror window.
+    fn mirror_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => {
+                if let Some(mirror) = &mut self.mirror {
+                    mirror.resize(&self.device, (size.width, size.height));
+                }
+            }
+            WindowEvent::CloseRequested => self.close_mirror(),
+            _ => (),
+        }
+    }
+
+    /// Asks the handler whether the canvas should close and exits the event loop if so.
+    ///
+    /// # Arguments
+    /// * `window_target` - The window target of the event loop for exiting the loop.
+    fn close(&mut self, window_target: &EventLoopWindowTarget<CanvasEvent>) {
+        if self.handler.get_mut().close_requested() {
+            window_target.exit();
+        } else {
+            info!("Closing the canvas has been vetoed by the handler");
+        }
+    }
+
+    /// Stops the handler and writes the frame timings once the event loop is exiting, no
+    /// matter whether the window has been closed, the handler requested to exit or an error
+    /// made the canvas quit.
+    fn stop(&mut self) {
+        stop_handler(self.handler.get_mut(), &mut self.profiler);
+    }
+
+    /// Returns true if the key event is the quit shortcut of the platform and quit shortcuts
+    /// are enabled.
+    ///
+    /// # Arguments
+    /// * `event` - The key event to check.
+    fn is_quit_shortcut(&self, event: &KeyEvent) -> bool {
+        if !self.quit_shortcuts || event.state != ElementState::Pressed || event.repeat {
+            return false;
+        }
+
+        if cfg!(target_os = "macos") {
+            event.physical_key == PhysicalKey::Code(KeyCode::KeyQ)
+                && self.modifiers == ModifiersState::SUPER
+        } else {
+            event.physical_key == PhysicalKey::Code(KeyCode::F4)
+                && self.modifiers == ModifiersState::ALT
+        }
+    }
+
+    /// Returns true if the event should be captured.
+    ///
+    /// # Arguments
+    /// * `event` - The event to check.
+    fn input(&mut self, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Returns true if a mouse button is held down, i.e., a drag operation is active.
+    fn is_dragging(&self) -> bool {
+        !self.pressed_buttons.is_empty()
+    }
+
+    /// Updates the cursor position and forwards it to the handler.
+    ///
+    /// # Arguments
+    /// * `position` - The new cursor position in physical coordinates.
+    fn cursor_moved(&mut self, position: winit::dpi::PhysicalPosition<f64>) {
+        if self.is_dragging() && !self.cursor_inside {
+            self.drag_captured = true;
+        }
+
+        let logical_position: LogicalPosition<f64> =
+            LogicalPosition::from_physical(position, self.window.scale_factor());
+
+        self.cursor_pos = [logical_position.x, logical_position.y];
+        let [x, y] = self.reported_cursor_pos();
+        self.dispatch_input(InputEvent::CursorMoved { x, y });
+    }
+
+    /// Converts the position of the touch into logical coordinates relative to the configured
+    /// origin like the cursor and forwards the touch to the handler.
+    ///
+    /// # Arguments
+    /// * `touch` - The touch event of the window.
+    fn touch(&mut self, touch: Touch) {
+        let position: LogicalPosition<f64> =
+            LogicalPosition::from_physical(touch.location, self.window.scale_factor());
+
+        self.dispatch_input(InputEvent::Touch {
+            phase: touch.phase,
+            id: touch.id,
+            x: position.x,
+            y: self.flip_cursor_y(position.y),
+        });
+    }
+
+    /// Returns the cursor position relative to the configured origin in logical coordinates.
+    fn reported_cursor_pos(&self) -> [f64; 2] {
+        let [x, y] = self.cursor_pos;
+        [x, self.flip_cursor_y(y)]
+    }
+
+    /// Converts a cursor position relative to the configured origin back into logical
+    /// coordinates with the top-left origin.
+    ///
+    /// # Arguments
+    /// * `x` - The x coordinate relative to the configured origin.
+    /// * `y` - The y coordinate relative to the configured origin.
+    fn unreported_cursor_pos(&self, x: f64, y: f64) -> [f64; 2] {
+        [x, self.flip_cursor_y(y)]
+    }
+
+    /// Flips the given y coordinate if the configured origin is the bottom-left corner. The
+    /// conversion is its own inverse.
+    ///
+    /// # Arguments
+    /// * `y` - The y coordinate to convert.
+    fn flip_cursor_y(&self, y: f64) -> f64 {
+        match self.cursor_origin {
+            CursorOrigin::TopLeft => y,
+            CursorOrigin::BottomLeft => {
+                let height = self.size.height as f64 / self.window.scale_factor();
+                height - y
+            }
+        }
+    }
+
+    /// Updates the button state and forwards the mouse button event to the handler.
+    ///
+    /// # Arguments
+    /// * `button` - The pressed/released mouse button.
+    /// * `pressed` - If true the mouse button was pressed and released otherwise.
+    fn mouse_input(&mut self, button: MouseButton, pressed: bool) {
+        self.track_button(button, pressed);
+
+        let [x, y] = self.reported_cursor_pos();
+        if pressed {
+            self.count_click(button, [x, y]);
+        }
+
+        self.dispatch_input(InputEvent::MouseButton {
+            x,
+            y,
+            button,
+            pressed,
+            click_count: self.click_count,
+            modifiers: self.modifiers,
+        });
+    }
+
+    /// Counts the press of the given button into the current click sequence or starts a new
+    /// sequence if the interval or the distance since the last press is exceeded.
+    ///
+    /// # Arguments
+    /// * `button` - The pressed mouse button.
+    /// * `pos` - The reported cursor position of the press.
+    fn count_click(&mut self, button: MouseButton, pos: [f64; 2]) {
+        let now = self.control.now();
+
+        let continues = self
+            .last_click
+            .is_some_and(|(time, last_button, last_pos)| {
+                let distance = (pos[0] - last_pos[0]).hypot(pos[1] - last_pos[1]);
+
+                last_button == button
+                    && now.saturating_sub(time) <= self.double_click_interval
+                    && distance <= self.double_click_distance
+            });
+
+        self.click_count = if continues { self.click_count + 1 } else { 1 };
+        self.last_click = Some((now, button, pos));
+    }
+
+    /// Normalizes the scroll delta to lines and forwards it to the handler.
+    ///
+    /// # Arguments
+    /// * `delta` - The scroll delta in lines or physical pixels.
+    fn mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        let (delta_x, delta_y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (x, y),
+            MouseScrollDelta::PixelDelta(position) => {
+                let logical: LogicalPosition<f64> = position.to_logical(self.window.scale_factor());
+                (
+                    (logical.x / SCROLL_LINE_HEIGHT) as f32,
+                    (logical.y / SCROLL_LINE_HEIGHT) as f32,
+                )
+            }
+        };
+
+        self.dispatch_input(InputEvent::MouseWheel { delta_x, delta_y });
+    }
+
+    /// Updates the set of mouse buttons held down.
+    ///
+    /// # Arguments
+    /// * `button` - The pressed/released mouse button.
+    /// * `pressed` - If true the mouse button was pressed and released otherwise.
+    fn track_button(&mut self, button: MouseButton, pressed: bool) {
+        if pressed {
+            if !self.pressed_buttons.contains(&button) {
+                self.pressed_buttons.push(button);
+            }
+        } else {
+            self.pressed_buttons.retain(|b| *b != button);
+        }
+
+        if !self.is_dragging() {
+            self.drag_captured = false;
+        }
+    }
+
+    /// Updates the set of physical keys held down.
+    ///
+    /// # Arguments
+    /// * `key` - The pressed/released physical key.
+    /// * `state` - The new state of the key.
+    fn track_key(&mut self, key: KeyCode, state: ElementState) {
+        match state {
+            ElementState::Pressed => {
+                if !self.pressed_keys.contains(&key) {
+                    self.pressed_keys.push(key);
+                }
+            }
+            ElementState::Released => self.pressed_keys.retain(|k| *k != key),
+        }
+    }
+
+    /// Returns the input state tracked by the canvas.
+    fn input_state(&self) -> InputState {
+        InputState {
+            cursor: (self.cursor_pos[0], self.cursor_pos[1]),
+            pressed_buttons: self.pressed_buttons.clone(),
+            pressed_keys: self.pressed_keys.clone(),
+            modifiers: self.modifiers,
+        }
+    }
+
+    /// Replaces the tracked input state without dispatching any events.
+    ///
+    /// # Arguments
+    /// * `state` - The input state to restore.
+    fn restore_input_state(&mut self, state: InputState) {
+        self.cursor_pos = [state.cursor.0, state.cursor.1];
+        self.pressed_buttons = state.pressed_buttons;
+        self.pressed_keys = state.pressed_keys;
+        self.modifiers = state.modifiers;
+        self.drag_captured = false;
+
+        self.control.set_input_state(self.input_state());
+    }
+
+    /// Updates the tracked input state with the synthetic input events and dispatches them to
+    /// the handler.
+    ///
+    /// # Arguments
+    /// * `events` - The input events to dispatch.
+    fn inject_input(&mut self, events: Vec<InputEvent>) {
+        for event in events {
+            match &event {
+                InputEvent::CursorMoved { x, y } => {
+                    self.cursor_pos = self.unreported_cursor_pos(*x, *y);
+                }
+                InputEvent::MouseButton {
+                    x,
+                    y,
+                    button,
+                    pressed,
+                    modifiers,
+                    ..
+                } => {
+                    self.cursor_pos = self.unreported_cursor_pos(*x, *y);
+                    self.track_button(*button, *pressed);
+                    self.modifiers = *modifiers;
+                }
+                InputEvent::Keyboard { modifiers, .. } => self.modifiers = *modifiers,
+                InputEvent::RawKey {
+                    physical_key,
+                    state,
+                    ..
+                } => self.track_key(*physical_key, *state),
+                InputEvent::RawMouseMotion { .. }
+                | InputEvent::MouseWheel { .. }
+                | InputEvent::Touch { .. }
+                | InputEvent::PinchGesture { .. }
+                | InputEvent::RotationGesture { .. } => {}
+            }
+
+            self.dispatch_input(event);
+        }
+    }
+
+    /// Forwards the raw mouse motion to the handler and continues reporting cursor movement
+    /// while a drag is active and the cursor has left the window. The latter is only used if
+    /// the platform does not capture the pointer for the window itself.
+    ///
+    /// # Arguments
+    /// * `delta` - The raw mouse motion delta, which is roughly in physical pixels.
+    fn raw_mouse_motion(&mut self, delta: (f64, f64)) {
+        self.dispatch_input(InputEvent::RawMouseMotion {
+            delta_x: delta.0,
+            delta_y: delta.1,
+        });
+
+        if !self.is_dragging() || self.cursor_inside || self.drag_captured {
+            return;
+        }
+
+        let scale_factor = self.window.scale_factor();
+        self.cursor_pos[0] += delta.0 / scale_factor;
+        self.cursor_pos[1] += delta.1 / scale_factor;
+
+        let [x, y] = self.reported_cursor_pos();
+        self.dispatch_input(InputEvent::CursorMoved { x, y });
+    }
+
+    /// Forwards the input event to the handler, either to the respective input callback or to
+    /// the exclusive input capture if one is active.
+    ///
+    /// # Arguments
+    /// * `event` - The input event to dispatch.
+    fn dispatch_input(&mut self, event: InputEvent) {
+        self.control.set_input_state(self.input_state());
+
+        if self.control.is_paused() {
+            if self.paused_input == PausedInput::Defer {
+                self.deferred_input.push(event);
+            }
+            return;
+        }
+
+        if let Some(capture) = self.control.active_input_capture() {
+            self.handler.get_mut().captured_input(capture, &event);
+            return;
+        }
+
+        deliver_input(self.handler.get_mut(), event);
+    }
+
+    /// Prepares the next frame, i.e., recompiles the shaders modified on disk if hot reloading
+    /// is enabled and lets the handler advance its state.
+    fn update(&mut self) {
+        #[cfg(feature = "hot-reload")]
+        self.reload_shaders();
+
+        let Some(timestep) = self.fixed_timestep else {
+            let ctx = self.render_context();
+            self.handler.borrow_mut().update(&ctx, self.delta_time);
+            return;
+        };
+
+        self.update_accumulator += Duration::from_secs_f32(self.delta_time);
+        let mut steps = 0;
+        while self.update_accumulator >= timestep && steps < MAX_FIXED_STEPS {
+            let ctx = self.render_context();
+            self.handler
+                .borrow_mut()
+                .update(&ctx, timestep.as_secs_f32());
+            self.update_accumulator -= timestep;
+            steps += 1;
+        }
+
+        // drop the time that can't be caught up with instead of piling up more updates
+        if self.update_accumulator >= timestep {
+            debug!("Fixed updates fell behind by {:?}", self.update_accumulator);
+            self.update_accumulator = Duration::ZERO;
+        }
+    }
+
+    /// Returns the fraction of a fixed time step the current frame lies between the last and
+    /// the next update, or 1.0 if the updates don't run at a fixed rate.
+    fn interpolation_alpha(&self) -> f32 {
+        self.fixed_timestep.map_or(1.0, |timestep| {
+            self.update_accumulator.as_secs_f32() / timestep.as_secs_f32()
+        })
+    }
+
+    /// Recompiles the shaders modified on disk and notifies the handler. Compile errors are
+    /// reported to the handler without interrupting rendering.
+    #[cfg(feature = "hot-reload")]
+    fn reload_shaders(&mut self) {
+        let mut reloaded = false;
+        for result in self.shader_watcher.poll(&self.device) {
+            match result {
+                Ok(()) => reloaded = true,
+                Err(e) => {
+                    warn!("{}", e);
+                    self.handler.get_mut().render_error(&e);
+                }
+            }
+        }
+
+        if reloaded {
+            info!("Shaders reloaded");
+            let ctx = self.render_context();
+            self.handler.borrow_mut().shaders_reloaded(&ctx);
+        }
+    }
+
+    /// Updates and renders the next frame and recovers from errors if possible.
+    /// Returns the error of rendering the frame, if any.
+    ///
+    /// # Arguments
+    /// * `window_target` - The window target of the event loop for exiting the loop.
+    fn frame(&mut self, window_target: &EventLoopWindowTarget<CanvasEvent>) -> Result<()> {
+        let start = Instant::now();
+        self.next_frame_start = self.frame_interval.map(|interval| start + interval);
+        self.update_delta_time();
+        self.apply_requested_surface_config();
+        self.update_scene_target();
+        self.apply_pending_resize(false);
+        self.update();
+
+        let frame_index = self.frame_index;
+        let result = self.redraw();
+
+        if self.profiler.is_enabled() {
+            let timing = FrameTiming {
+                frame_index,
+                cpu_time: start.elapsed(),
+                gpu_time: self.gpu_time.take(),
+            };
+
+            if self.profiler.record(timing) {
+                self.handler.get_mut().frame_timing(&timing);
+            }
+        }
+
+        if let Err(e) = &result {
+            error!("{:?}", e);
+
+            let action = match e {
+                Error::ContextLost(_) => self.error_policy.on_context_lost(e),
+                Error::Outdated(_) => self.error_policy.on_outdated(e),
+                Error::Timeout(_) => self.error_policy.on_timeout(e),
+                Error::OutOfMemory(_) => self.error_policy.on_out_of_memory(e),
+                _ => self.error_policy.on_error(e),
+            };
+
+            match action {
+                RecoveryAction::Reconfigure => self.resize(self.size),
+                RecoveryAction::Skip => {}
+                RecoveryAction::Recover => {
+                    if let Err(err) = self.recover() {
+                        error!("Failed to recover: {}", err);
+                        self.handler.get_mut().render_error(&err);
+                        self.fatal_error = Some(err);
+                        window_target.exit();
+                    }
+                }
+                RecoveryAction::Exit => {
+                    self.fatal_error = Some(e.clone());
+                    window_target.exit();
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Updates the time since the previous frame from the time source. The first frame and
+    /// the first frame after a pause have a delta time of zero.
+    fn update_delta_time(&mut self) {
+        let now = self.control.now();
+        self.delta_time = self
+            .last_frame_time
+            .map_or(0.0, |last| now.saturating_sub(last).as_secs_f32());
+        self.last_frame_time = Some(now);
+        self.frame_stats.record(self.delta_time);
+    }
+
+    /// Renders the next frame and reports whether it has been presented.
+    fn redraw(&mut self) -> Result<()> {
+        let result = self.render();
+
+        let presented = matches!(result, Ok(true));
+        self.control.set_last_frame_presented(presented);
+        self.handler.get_mut().frame_presented(presented);
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(err) => {
+                self.handler.get_mut().render_error(&err);
+                Err(err)
+            }
+        }
+    }
+
+    /// Acquires the next surface texture. Timeouts are tolerated up to the configured number of
+    /// consecutive occurrences by skipping the frame and scheduling a retry.
+    ///
+    /// Returns `None` if the frame is skipped.
+    fn acquire_surface_texture(&mut self) -> Result<Option<wgpu::SurfaceTexture>> {
+        match self.surface.get_current_texture() {
+            Ok(output) => {
+                self.consecutive_timeouts = 0;
+                Ok(Some(output))
+            }
+            Err(wgpu::SurfaceError::Timeout)
+                if self.consecutive_timeouts < self.max_consecutive_timeouts =>
+            {
+                self.consecutive_timeouts += 1;
+                warn!(
+                    "Timeout while acquiring the surface texture, skipping frame ({}/{})",
+                    self.consecutive_timeouts, self.max_consecutive_timeouts
+                );
+
+                self.window.request_redraw();
+                Ok(None)
+            }
+            // the surface no longer matches the window, which is expected while resizing with
+            // a throttle, so apply the latest size right away
+            Err(wgpu::SurfaceError::Outdated) if self.pending_resize.is_some() => {
+                self.apply_pending_resize(true);
+                self.window.request_redraw();
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Renders and presents the next frame.
+    /// Returns false if the frame has been skipped.
+    fn render(&mut self) -> Result<bool> {
+        self.update_frame_attachments();
+
+        let output = match self.acquire_surface_texture()? {
+            Some(output) => output,
+            None => return Ok(false),
+        };
+
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+
+        // the view the content is rendered into
+        let target_view = match (&self.scaler, &self.preserved_target) {
+            (Some(scaler), _) => scaler.view(),
+            (None, Some(target)) => target.view(),
+            (None, None) => &view,
+        };
+
+        // give the handler the chance to take over the whole frame
+        let ctx = self.render_context();
+        let mut handler = self.handler.borrow_mut();
+
+        // the scene is rendered into its own target if its resolution is decoupled
+        let frame_view = match &self.scene {
+            Some(scene) => scene.view(),
+            None => target_view,
+        };
+        let custom_frame = handler.on_frame(&ctx, frame_view);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        // true if the default pass writes the timestamps of the GPU timer
+        let mut timed = false;
+        if let Some(result) = custom_frame {
+            result.map_err(|e| Error::Internal(format!("Error during frame: {}", e)))?;
+        } else if let Some(result) = handler.encode_frame(&ctx, &mut encoder, frame_view) {
+            result.map_err(|e| Error::Internal(format!("Error during frame: {}", e)))?;
+        } else {
+            // draw on top of the previous frame if the contents are preserved
+            let load = if self.preserve_contents && !self.clear_pending {
+                wgpu::LoadOp::Load
+            } else {
+                wgpu::LoadOp::Clear(self.clear_color)
+            };
+
+            let timestamp_writes = self
+                .gpu_timer
+                .as_ref()
+                .and_then(|timer| timer.timestamp_writes());
+            timed = timestamp_writes.is_some();
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                color_attachments: &[Some(color_attachment(
+                    frame_view,
+                    self.msaa_target.as_ref(),
+                    load,
+                ))],
+                depth_stencil_attachment: self.depth_target.as_ref().zip(self.depth).map(
+                    |(target, depth)| depth_stencil_attachment(target, depth, self.clear_stencil),
+                ),
+                occlusion_query_set: None,
+                timestamp_writes,
+            });
+
+            handler.next_frame(&ctx, &mut render_pass);
+        }
+
+        // composite the scene before the overlay, whose commands are submitted by the handler
+        if let Some(scene) = &self.scene {
+            scene.composite(&mut encoder, target_view);
+            self.queue.submit(std::iter::once(encoder.finish()));
+
+            handler.on_overlay(&ctx, target_view);
+
+            encoder = self
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Render Encoder"),
+                });
+        }
+
+        drop(handler);
+        self.clear_pending = false;
+        if let (true, Some(timer)) = (timed, &mut self.gpu_timer) {
+            timer.resolve(&mut encoder);
+        }
+
+        #[cfg(feature = "text")]
+        self.text_overlay.render(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            target_view,
+            self.target_size(),
+        )?;
+
+        // the mirror shows the offscreen target, which always exists while mirroring
+        let offscreen_view = match (&self.scaler, &self.preserved_target) {
+            (Some(scaler), _) => Some(scaler.view()),
+            (None, Some(target)) => Some(target.view()),
+            (None, None) => None,
+        };
+        let mirror_output = match (&self.mirror, offscreen_view) {
+            (Some(mirror), Some(source)) => {
+                mirror.draw(&self.device, &mut encoder, source, self.target_size())?
+            }
+            _ => None,
+        };
+
+        if let Some(scaler) = &self.scaler {
+            scaler.draw(&mut encoder, &view);
+        } else if let Some(target) = &self.preserved_target {
+            target.present(&mut encoder, &view);
+        }
+
+        let screenshot = self.record_screenshot(&mut encoder, &output.texture);
+
+        // submit will accept anything that implements IntoIter
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        if let Some(timer) = &mut self.gpu_timer {
+            timer.map();
+        }
+
+        match screenshot {
+            Some(Ok(mut readback)) => {
+                readback.map();
+                self.screenshot = Some(readback);
+            }
+            Some(Err(e)) => {
+                error!("{}", e);
+                self.handler.get_mut().render_error(&e);
+            }
+            None => {}
+        }
+
+        // all windows share one submission and are presented in a fixed order, the canvas first
+        output.present();
+        if let Some(mirror_output) = mirror_output {
+            mirror_output.present();
+        }
+
+        self.frame_index += 1;
+
+        Ok(true)
+    }
+
+    /// Handles an event of the event loop.
+    ///
+    /// # Arguments
+    /// * `event` - The event to handle.
+    /// * `window_target` - The window target of the event loop, e.g., for exiting the loop.
+    fn handle_event(
+        &mut self,
+        event: Event<CanvasEvent>,
+        window_target: &EventLoopWindowTarget<CanvasEvent>,
+    ) {
+        match event {
+            Event::WindowEvent { event, window_id }
+                if window_id == self.window().id() && !self.input(&event) =>
+            {
+                match event {
+                    WindowEvent::Resized(size) => {
+                        self.minimized = size.width == 0 || size.height == 0;
+                        self.update_suspension(window_target);
+                        self.resized(size);
+                    }
+                    WindowEvent::Occluded(occluded) => {
+                        self.occluded = occluded;
+                        self.update_suspension(window_target);
+                    }
+                    WindowEvent::Focused(focused) => {
+                        self.focused = focused;
+                        self.handler.get_mut().focus_changed(focused);
+                        self.update_suspension(window_target);
+                    }
+                    WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                        info!("Scale factor changed to {}", scale_factor);
+                        self.handler.get_mut().scale_factor_changed(scale_factor);
+                    }
+                    WindowEvent::CursorMoved { position, .. } => {
+                        self.cursor_moved(position);
+                    }
+                    WindowEvent::CursorEntered { .. } => {
+                        self.cursor_inside = true;
+                    }
+                    WindowEvent::CursorLeft { .. } => {
+                        self.cursor_inside = false;
+                    }
+                    WindowEvent::MouseInput { state, button, .. } => {
+                        let pressed: bool = state == ElementState::Pressed;
+                        self.mouse_input(button, pressed);
+                    }
+                    WindowEvent::MouseWheel { delta, .. } => {
+                        self.mouse_wheel(delta);
+                    }
+                    WindowEvent::Touch(touch) => {
+                        self.touch(touch);
+                    }
+                    WindowEvent::TouchpadMagnify { delta, phase, .. } => {
+                        self.dispatch_input(InputEvent::PinchGesture { delta, phase });
+                    }
+                    WindowEvent::TouchpadRotate { delta, phase, .. } => {
+                        self.dispatch_input(InputEvent::RotationGesture { delta, phase });
+                    }
+                    WindowEvent::ModifiersChanged(modifiers) => {
+                        self.modifiers = modifiers.state();
+                        self.control.set_input_state(self.input_state());
+                    }
+                    WindowEvent::KeyboardInput { event, .. } if self.is_quit_shortcut(&event) => {
+                        self.close(window_target);
+                    }
+                    WindowEvent::KeyboardInput { event, .. } => {
+                        if let PhysicalKey::Code(physical_key) = event.physical_key {
+                            self.track_key(physical_key, event.state);
+                            self.dispatch_input(InputEvent::RawKey {
+                                physical_key,
+                                state: event.state,
+                                repeat: event.repeat,
+                            });
+                        }
+
+                        let pressed = event.state == ElementState::Pressed;
+                        self.dispatch_input(InputEvent::Keyboard {
+                            key: event.logical_key,
+                            physical: event.physical_key,
+                            pressed,
+                            repeat: event.repeat,
+                            modifiers: self.modifiers,
+                        });
+                    }
+                    WindowEvent::DroppedFile(path) => self.handler.get_mut().file_dropped(path),
+                    WindowEvent::HoveredFile(path) => self.handler.get_mut().file_hovered(path),
+                    WindowEvent::HoveredFileCancelled => {
+                        self.handler.get_mut().file_hover_cancelled()
+                    }
+                    WindowEvent::CloseRequested => self.close(window_target),
+                    WindowEvent::RedrawRequested
+                        if !self.control.is_paused() && !self.suspended =>
+                    {
+                        // errors are already reported by the frame itself
+                        let _ = self.frame(window_target);
+                    }
+                    _ => (),
+                }
+            }
+            Event::WindowEvent { event, window_id } if self.is_mirror(window_id) => {
+                self.mirror_event(&event);
+            }
+            Event::DeviceEvent {
+                event: DeviceEvent::MouseMotion { delta },
+                ..
+            } => {
+                self.raw_mouse_motion(delta);
+            }
+            Event::UserEvent(CanvasEvent::RenderNow(sender)) => {
+                // the requester may have stopped waiting, which is fine
+                let _ = sender.send(self.frame(window_target));
+            }
+            Event::UserEvent(CanvasEvent::InjectInput(events)) => {
+                self.inject_input(events);
+            }
+            Event::UserEvent(CanvasEvent::RestoreInputState(state)) => {
+                self.restore_input_state(state);
+            }
+            #[cfg(feature = "assets")]
+            Event::UserEvent(CanvasEvent::AssetLoaded(id, asset)) => {
+                self.asset_loaded(id, asset);
+            }
+            Event::AboutToWait if self.control.take_exit_request() => {
+                info!("Exit has been requested by the handler");
+                window_target.exit();
+            }
+            Event::LoopExiting => self.stop(),
+            Event::AboutToWait => {
+                self.update_mirror(window_target);
+                self.deliver_deferred_input();
+                self.poll_screenshot();
+                self.poll_gpu_timer();
+                self.handler().idle();
+
+                if !self.control.is_paused() && !self.suspended {
+                    self.schedule_frame(window_target);
+                } else {
+                    // the time spent paused doesn't count into the delta time
+                    self.last_frame_time = None;
+
+                    // don't keep waking up for a frame limited by the frame rate while paused
+                    if !self.suspended && self.next_frame_start.take().is_some() {
+                        window_target.set_control_flow(self.render_mode.control_flow());
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Passes the input event to the respective input callback of the handler.
+///
+/// # Arguments
+/// * `handler` - The handler receiving the event.
+/// * `event` - The input event to deliver.
+fn deliver_input<H: EventHandler>(handler: &mut H, event: InputEvent) {
+    match event {
+        InputEvent::CursorMoved { x, y } => handler.cursor_move(x, y),
+        InputEvent::MouseButton {
+            x,
+            y,
+            button,
+            pressed,
+            click_count,
+            modifiers,
+        } => handler.mouse_button(x, y, button, pressed, click_count, modifiers),
+        InputEvent::RawMouseMotion { delta_x, delta_y } => {
+            handler.raw_mouse_motion(delta_x, delta_y)
+        }
+        InputEvent::Touch { phase, id, x, y } => handler.touch(phase, id, x, y),
+        InputEvent::PinchGesture { delta, phase } => handler.pinch_gesture(delta, phase),
+        InputEvent::RotationGesture { delta, phase } => handler.rotation_gesture(delta, phase),
+        InputEvent::MouseWheel { delta_x, delta_y } => handler.mouse_wheel(delta_x, delta_y),
+        InputEvent::Keyboard {
+            key,
+            physical,
+            pressed,
+            repeat,
+            modifiers,
+        } => handler.keyboard_event(key, physical, pressed, repeat, modifiers),
+        InputEvent::RawKey {
+            physical_key,
+            state,
+            repeat,
+        } => handler.raw_key(physical_key, state, repeat),
+    }
+}
+
+/// Stops the handler and writes the frame timings.
+///
+/// # Arguments
+/// * `handler` - The handler to stop.
+/// * `profiler` - The profiler with the recorded frame timings.
+fn stop_handler<H: EventHandler>(handler: &mut H, profiler: &mut FrameProfiler) {
+    handler.stop();
+    if let Err(e) = profiler.flush() {
+        error!("Failed to write the frame timings: {}", e);
+    }
+}
+
+/// Everything needed for creating the canvas data once the window has been created.
+struct CanvasSetup<H: EventHandler> {
+    options: CanvasOptions,
+    window: Rc<Window>,
+    handler: H,
+    controller: CanvasController,
+}
+
+impl<H: EventHandler> CanvasSetup<H> {
+    /// Creates the wgpu objects for the window and calls the setup of the handler. The handler
+    /// is stopped if this fails.
+    async fn create(mut self) -> Result<CanvasData<H>> {
+        let error_policy =
+            std::mem::replace(&mut self.options.error_policy, Box::new(DefaultErrorPolicy));
+        let time_source = std::mem::replace(
+            &mut self.options.time_source,
+            Box::new(MonotonicClock::new()),
+        );
+
+        let mut handler = Some(self.handler);
+        let result = CanvasData::new(
+            &self.options,
+            self.window,
+            &mut handler,
+            error_policy,
+            time_source,
+            self.controller,
+        )
+        .await;
+
+        // the event loop won't run, so the handler is stopped right away
+        let mut canvas_data = match (result, handler) {
+            (Ok(canvas_data), _) => canvas_data,
+            (Err(err), Some(mut handler)) => {
+                handler.stop();
+                return Err(err);
+            }
+            (Err(err), None) => return Err(err),
+        };
+        if let Err(err) = canvas_data.setup_handler() {
+            error!("Error during setup: {}", err);
+            canvas_data.stop();
+            return Err(Error::Internal(format!("Error during setup: {}", err)));
+        }
+
+        Ok(canvas_data)
+    }
+}
+
+/// Creates the device and the command queue with the features and limits of the options.
+///
+/// # Arguments
+/// * `options` - The options with the requested features and limits.
+/// * `adapter` - The adapter to create the device from.
+pub(crate) async fn request_device(
+    options: &CanvasOptions,
+    adapter: &wgpu::Adapter,
+) -> Result<(wgpu::Device, wgpu::Queue)> {
+    let missing_features = options.features - adapter.features();
+    if !missing_features.is_empty() {
+        return Err(Error::GraphicsAPI(format!(
+            "Adapter doesn't support the requested features: {:?}",
+            missing_features
+        )));
+    }
+
+    // the timestamp queries for the GPU timing are only requested if they are supported
+    let mut features = options.features;
+    if options.gpu_timing && adapter.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+        features |= wgpu::Features::TIMESTAMP_QUERY;
+    }
+
+    let adapter_limits = adapter.limits();
+    if !options.limits.check_limits(&adapter_limits) {
+        return Err(Error::GraphicsAPI(format!(
+            "Adapter doesn't support the requested limits {:?}, supported limits are {:?}",
+            options.limits, adapter_limits
+        )));
+    }
+
+    if let Some(trace_path) = &options.trace_path {
+        if trace_path.is_dir() {
+            info!("Recording wgpu trace to {}", trace_path.to_string_lossy());
+        } else {
+            warn!(
+                "The trace directory {} doesn't exist",
+                trace_path.to_string_lossy()
+            );
+        }
+    }
+
+    adapter
+        .request_device(
+            &wgpu::DeviceDescriptor {
+                features,
+                limits: options.limits.clone(),
+                label: None,
+            },
+            options.trace_path.as_deref(),
+        )
+        .await
+        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))
+}
+
+/// Checks that the depth configuration and the clear values for the depth and stencil
+/// buffer are valid.
+///
+/// # Arguments
+/// * `options` - The options with the clear values.
+pub(crate) fn check_clear_values(options: &CanvasOptions) -> Result<()> {
+    if let Some(depth) = &options.depth {
+        check_depth_format(depth.format)?;
+
+        if !(0.0..=1.0).contains(&depth.clear_value) {
+            return Err(Error::Internal(format!(
+                "Invalid clear depth {}, must be in the range [0, 1]",
+                depth.clear_value
+            )));
+        }
+    }
+
+    if options.clear_stencil > u8::MAX as u32 {
+        return Err(Error::Internal(format!(
+            "Invalid clear stencil {}, must be in the range [0, {}]",
+            options.clear_stencil,
+            u8::MAX
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks if the adapter supports the downlevel capabilities required by the options.
+///
+/// # Arguments
+/// * `options` - The options with the required capabilities.
+/// * `adapter` - The adapter to check.
+pub(crate) fn check_downlevel_capabilities(
+    options: &CanvasOptions,
+    adapter: &wgpu::Adapter,
+) -> Result<()> {
+    let capabilities = adapter.get_downlevel_capabilities();
+
+    let missing_flags = options.required_downlevel_flags - capabilities.flags;
+    if !missing_flags.is_empty() {
+        return Err(Error::GraphicsAPI(format!(
+            "Adapter is missing the required downlevel capabilities: {:?}",
+            missing_flags
+        )));
+    }
+
+    if capabilities.shader_model < options.required_shader_model {
+        return Err(Error::GraphicsAPI(format!(
+            "Adapter only supports shader model {:?}, but {:?} is required",
+            capabilities.shader_model, options.required_shader_model
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the requested sample count if the color and the depth format of the frame
+/// support it, and 1 otherwise.
+///
+/// # Arguments
+/// * `requested` - The sample count requested in the options.
+/// * `adapter` - The adapter the device has been created from.
+/// * `device` - The device used for rendering.
+/// * `color_format` - The format of the frame.
+/// * `depth_format` - The format of the depth buffer, if any.
+pub(crate) fn select_sample_count(
+    requested: u32,
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    color_format: wgpu::TextureFormat,
+    depth_format: Option<wgpu::TextureFormat>,
+) -> Result<u32> {
+    if requested == 0 {
+        return Err(Error::Internal("Invalid sample count 0".to_string()));
+    }
+
+    if requested == 1 {
+        return Ok(1);
+    }
+
+    // counts beyond the guaranteed ones need features which the device doesn't enable
+    let supported = |format: wgpu::TextureFormat| {
+        let guaranteed = format.guaranteed_format_features(device.features());
+        let features = adapter.get_texture_format_features(format);
+
+        guaranteed.flags.sample_count_supported(requested)
+            && features.flags.sample_count_supported(requested)
+    };
+
+    if supported(color_format) && depth_format.is_none_or(supported) {
+        return Ok(requested);
+    }
+
+    warn!(
+        "Sample count {} isn't supported for the frame, falling back to 1",
+        requested
+    );
+
+    Ok(1)
+}
+
+/// Lets the given function choose among the adapters compatible with the surface.
+///
+/// # Arguments
+/// * `choose_adapter` - The function returning the index of the chosen adapter.
+/// * `instance` - The instance for enumerating the adapters.
+/// * `surface` - The surface the adapter must be compatible with, or `None` in headless mode.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn choose_adapter(
+    choose_adapter: fn(&[wgpu::AdapterInfo]) -> usize,
+    instance: &wgpu::Instance,
+    surface: Option<&wgpu::Surface>,
+) -> Result<Option<wgpu::Adapter>> {
+    let mut adapters: Vec<wgpu::Adapter> = instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .filter(|adapter| surface.is_none_or(|surface| adapter.is_surface_supported(surface)))
+        .collect();
+    if adapters.is_empty() {
+        return Err(Error::GraphicsAPI("No suitable adapter found".to_string()));
+    }
+
+    let infos: Vec<wgpu::AdapterInfo> = adapters.iter().map(|a| a.get_info()).collect();
+    let index = choose_adapter(&infos);
+    if index >= adapters.len() {
+        return Err(Error::Internal(format!(
+            "Chosen adapter index {} is out of range, only {} adapters are available",
+            index,
+            adapters.len()
+        )));
+    }
+
+    Ok(Some(adapters.swap_remove(index)))
+}
+
+/// Adapters can't be enumerated on the web, so the default selection is used.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn choose_adapter(
+    _choose_adapter: fn(&[wgpu::AdapterInfo]) -> usize,
+    _instance: &wgpu::Instance,
+    _surface: Option<&wgpu::Surface>,
+) -> Result<Option<wgpu::Adapter>> {
+    warn!("Choosing the adapter isn't supported on the web, using the default selection");
+    Ok(None)
+}
+
+/// Begins a render pass into the given view that clears it to the given color, e.g., for the
+/// last pass of `EventHandler::encode_frame`. The pass has neither a depth buffer nor
+/// multisampling, so the pipelines drawing into it must use a sample count of one.
+///
+/// # Arguments
+/// * `encoder` - The encoder to record the pass into.
+/// * `view` - The view of the texture to render into, e.g., the one of the frame.
+/// * `clear_color` - The color to clear the view to, e.g., `CLEAR_COLOR`.
+pub fn begin_default_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    view: &'a wgpu::TextureView,
+    clear_color: wgpu::Color,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear_color),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    })
+}
+
+/// Returns the color attachment of the default render pass, which renders into the
+/// multisampled texture and resolves it into the view if multisampling is enabled.
+///
+/// # Arguments
+/// * `view` - The view of the texture the frame is rendered into.
+/// * `msaa_target` - The multisampled color texture, if any.
+/// * `load` - The operation at the start of the pass, i.e., clearing or keeping the frame.
+pub(crate) fn color_attachment<'a>(
+    view: &'a wgpu::TextureView,
+    msaa_target: Option<&'a Texture>,
+    load: wgpu::LoadOp<wgpu::Color>,
+) -> wgpu::RenderPassColorAttachment<'a> {
+    let ops = wgpu::Operations {
+        load,
+        store: wgpu::StoreOp::Store,
+    };
+
+    match msaa_target {
+        Some(msaa) => wgpu::RenderPassColorAttachment {
+            view: &msaa.view,
+            resolve_target: Some(view),
+            ops,
+        },
+        None => wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops,
+        },
+    }
+}
+
+/// Returns the depth attachment of the default render pass, which clears the depth buffer as
+/// configured and its stencil aspect, if any, to the given value.
+///
+/// # Arguments
+/// * `target` - The depth buffer.
+/// * `depth` - The configuration of the depth buffer.
+/// * `clear_stencil` - The value the stencil aspect is cleared to.
+pub(crate) fn depth_stencil_attachment(
+    target: &Texture,
+    depth: DepthConfig,
+    clear_stencil: u32,
+) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+    wgpu::RenderPassDepthStencilAttachment {
+        view: &target.view,
+        depth_ops: Some(depth.clear_ops()),
+        stencil_ops: depth
+            .format
+            .has_stencil_aspect()
+            .then_some(wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear_stencil),
+                store: wgpu::StoreOp::Store,
+            }),
+    }
+}
+
+/// Returns the given render attachment if it matches the frame, and creates a new one
+/// otherwise.
+///
+/// # Arguments
+/// * `device` - The device used for rendering.
+/// * `attachment` - The current attachment, if any.
+/// * `label` - The debug label of the attachment.
+/// * `size` - The width and height of the frame.
+/// * `format` - The format of the attachment.
+/// * `sample_count` - The number of samples per pixel.
+pub(crate) fn update_attachment(
+    device: &wgpu::Device,
+    attachment: Option<Texture>,
+    label: &str,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+) -> Texture {
+    if let Some(attachment) = attachment {
+        let texture = &attachment.texture;
+        if (texture.width(), texture.height()) == size && texture.format() == format {
+            return attachment;
+        }
+    }
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    Texture { texture, view }
+}
+
+/// Sets the application id of the window on Linux and other free unix platforms.
+///
+/// # Arguments
+/// * `builder` - The builder of the window.
+/// * `app_id` - The application id, if any.
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+))]
+fn with_app_id(builder: WindowBuilder, app_id: Option<&str>) -> WindowBuilder {
+    use winit::platform::x11::WindowBuilderExtX11;
+
+    // X11 and Wayland share the same application name in the builder
+    match app_id {
+        Some(app_id) => WindowBuilderExtX11::with_name(builder, app_id, app_id),
+        None => builder,
+    }
+}
+
+/// The application id is only supported on Linux and other free unix platforms.
+#[cfg(not(all(
+    unix,
+    not(any(target_os = "macos", target_os = "ios", target_os = "android"))
+)))]
+fn with_app_id(builder: WindowBuilder, _app_id: Option<&str>) -> WindowBuilder {
+    builder
+}
+
+/// Creates the event loop and the window with the given options.
+///
+/// # Arguments
+/// * `options` - The options for creating the window.
+fn create_window(options: &CanvasOptions) -> Result<(EventLoop<CanvasEvent>, Window)> {
+    debug!("Create event loop...");
+    let event_loop = EventLoopBuilder::<CanvasEvent>::with_user_event()
+        .build()
+        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+    // the render mode determines whether the event loop keeps rendering or waits for events
+    event_loop.set_control_flow(options.render_mode.control_flow());
+
+    debug!("Create window...");
+    if let (Some(min_size), Some(max_size)) = (options.min_size, options.max_size) {
+        if min_size.0 > max_size.0 || min_size.1 > max_size.1 {
+            return Err(Error::Internal(format!(
+                "Invalid window size constraints: minimum {}x{} exceeds maximum {}x{}",
+                min_size.0, min_size.1, max_size.0, max_size.1
+            )));
+        }
+    }
+
+    let to_size = |(width, height): (u32, u32)| -> Size {
+        if options.size_is_physical {
+            PhysicalSize::new(width, height).into()
+        } else {
+            LogicalSize::new(width, height).into()
+        }
+    };
+    let size = to_size((options.width, options.height));
+
+    let icon = options.icon.as_ref().map(WindowIcon::to_icon).transpose()?;
+    let window_builder = WindowBuilder::new()
+        .with_title(&options.title)
+        .with_inner_size(size)
+        .with_maximized(options.fit_to_work_area)
+        .with_decorations(options.decorations)
+        .with_resizable(options.resizable)
+        .with_transparent(options.transparent)
+        .with_fullscreen(options.fullscreen.then_some(Fullscreen::Borderless(None)))
+        .with_window_icon(icon);
+    let window_builder = match options.min_size {
+        Some(min_size) => window_builder.with_min_inner_size(to_size(min_size)),
+        None => window_builder,
+    };
+    let window_builder = match options.position {
+        Some((x, y)) if options.size_is_physical => {
+            window_builder.with_position(Position::from(PhysicalPosition::new(x, y)))
+        }
+        Some((x, y)) => window_builder.with_position(Position::from(LogicalPosition::new(x, y))),
+        None => window_builder,
+    };
+    let window_builder = match options.max_size {
+        Some(max_size) => window_builder.with_max_inner_size(to_size(max_size)),
+        None => window_builder,
+    };
+
+    let window = with_app_id(window_builder, options.app_id.as_deref())
+        .build(&event_loop)
+        .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+
+    Ok((event_loop, window))
+}
+
+/// Creates the canvas and runs it until the window is closed. This is the asynchronous entry
+/// point for callers that already drive an executor, e.g., on the web. Native applications
+/// usually call `create_and_run_canvas_blocking` or `run_main` instead, which don't need an
+/// async runtime.
+///
+/// Returns an error if creating the canvas failed or the error policy exited the event loop
+/// because of an error.
+///
+/// # Arguments
+/// * `options` - The options for creating the canvas.
+/// * `handler` - The event handler for the canvas.
+pub async fn create_and_run_canvas<H>(options: CanvasOptions, mut handler: H) -> Result<()>
+where
+    H: EventHandler,
+{
+    info!("Creating canvas...");
+
+    // the event loop won't run, so the handler is stopped right away
+    let (event_loop, window) = match create_window(&options) {
+        Ok(created) => created,
+        Err(err) => {
+            handler.stop();
+            return Err(err);
+        }
+    };
+    let window = Rc::new(window);
+
+    // the surface can't be created before the window is shown on some platforms, which is
+    // signaled by the first resume or redraw request, whereas blocking on the setup isn't
+    // possible on the web
+    let defer_surface_creation = options.defer_surface_creation && !cfg!(target_arch = "wasm32");
+
+    let mut setup = Some(CanvasSetup {
+        options,
+        window,
+        handler,
+        controller: CanvasController::new(event_loop.create_proxy()),
+    });
+
+    let mut canvas_data = None;
+    if !defer_surface_creation {
+        if let Some(setup) = setup.take() {
+            canvas_data = Some(setup.create().await?);
+        }
+    }
+
+    let mut setup_error = None;
+    let result = event_loop.run(|event, window_target| {
+        let shown = matches!(
+            event,
+            Event::Resumed
+                | Event::WindowEvent {
+                    event: WindowEvent::RedrawRequested,
+                    ..
+                }
+        );
+
+        if shown {
+            if let Some(setup) = setup.take() {
+                match pollster::block_on(setup.create()) {
+                    Ok(data) => canvas_data = Some(data),
+                    Err(err) => {
+                        setup_error = Some(err);
+                        window_target.exit();
+                    }
+                }
+            }
+        }
+
+        if let Some(canvas_data) = &mut canvas_data {
+            canvas_data.handle_event(event, window_target);
+        }
+    });
+
+    // the loop exited before the window has been shown, so the canvas has never been created
+    if let Some(mut setup) = setup.take() {
+        setup.handler.stop();
+    }
+    result.map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+
+    if let Some(err) = setup_error {
+        return Err(err);
+    }
+
+    // the error the error policy exited the event loop for
+    match canvas_data.and_then(|canvas_data| canvas_data.fatal_error) {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// Creates the canvas and runs it until the window is closed, blocking the calling thread.
+/// This is the default entry point, which drives the asynchronous setup of the adapter and
+/// the device with `pollster`, so no async runtime is needed. Not supported on the web, where
+/// the thread can't be blocked.
+///
+/// Returns an error if creating the canvas failed or the error policy exited the event loop
+/// because of an error.
+///
+/// # Arguments
+/// * `options` - The options for creating the canvas.
+/// * `handler` - The event handler for the canvas.
+pub fn create_and_run_canvas_blocking<H>(options: CanvasOptions, handler: H) -> Result<()>
+where
+    H: EventHandler,
+{
+    pollster::block_on(create_and_run_canvas(options, handler))
+}
+
+/// Creates the canvas and runs it until the window is closed, blocking the calling thread.
+/// This is meant to be the last call of a `main` function, i.e., `fn main() -> ExitCode`.
+///
+/// Returns `ExitCode::SUCCESS` if the canvas terminated normally. Otherwise, the error is
+/// reported and `ExitCode::FAILURE` is returned.
+///
+/// # Arguments
+/// * `options` - The options for creating the canvas.
+/// * `handler` - The event handler for the canvas.
+pub fn run_main<H>(options: CanvasOptions, handler: H) -> ExitCode
+where
+    H: EventHandler,
+{
+    match create_and_run_canvas_blocking(options, handler) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // make sure the error is visible even if no logger has been initialized
+            if log_enabled!(Level::Error) {
+                error!("Canvas terminated with an error: {}", err);
+            } else {
+                eprintln!("Canvas terminated with an error: {}", err);
+            }
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as StdError;
+
+    use super::*;
+
+    /// A handler ignoring all callbacks except for recording the keyboard events and that it
+    /// has been stopped.
+    #[derive(Default)]
+    struct TestHandler {
+        stopped: bool,
+        /// The physical key, the pressed and the repeat flag of each keyboard event.
+        keys: Vec<(PhysicalKey, bool, bool)>,
+    }
+
+    impl EventHandler for TestHandler {
+        fn setup(
+            &mut self,
+            _ctx: &RenderContext,
+            _width: u32,
+            _height: u32,
+        ) -> std::result::Result<(), Box<dyn StdError>> {
+            Ok(())
+        }
+
+        fn stop(&mut self) {
+            self.stopped = true;
+        }
+
+        fn next_frame<'a>(&'a mut self, _ctx: &RenderContext, _pass: &mut wgpu::RenderPass<'a>) {}
+
+        fn resize(&mut self, _w: u32, _h: u32) {}
+
+        fn cursor_move(&mut self, _x: f64, _y: f64) {}
+
+        fn mouse_button(
+            &mut self,
+            _x: f64,
+            _y: f64,
+            _button: MouseButton,
+            _pressed: bool,
+            _click_count: u32,
+            _modifiers: ModifiersState,
+        ) {
+        }
+
+        fn keyboard_event(
+            &mut self,
+            _key: winit::keyboard::Key,
+            physical: PhysicalKey,
+            pressed: bool,
+            repeat: bool,
+            _modifiers: ModifiersState,
+        ) {
+            self.keys.push((physical, pressed, repeat));
+        }
+    }
+
+    type TestCanvas = CanvasData<TestHandler>;
+
+    #[test]
+    fn select_surface_format_prefers_srgb() {
+        let formats = [
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+        ];
+        let format =
+            TestCanvas::select_surface_format(false, SrgbFallback::WarnAndFallback, &formats);
+        assert_eq!(format.unwrap(), wgpu::TextureFormat::Bgra8UnormSrgb);
+    }
+
+    #[test]
+    fn select_surface_format_applies_srgb_fallback() {
+        let formats = [wgpu::TextureFormat::Bgra8Unorm];
+
+        let format =
+            TestCanvas::select_surface_format(false, SrgbFallback::WarnAndFallback, &formats);
+        assert_eq!(format.unwrap(), wgpu::TextureFormat::Bgra8Unorm);
+
+        let format = TestCanvas::select_surface_format(false, SrgbFallback::Error, &formats);
+        assert!(format.is_err());
+
+        let formats = [
+            wgpu::TextureFormat::Bgra8Unorm,
+            wgpu::TextureFormat::Bgra8UnormSrgb,
+        ];
+        let format = TestCanvas::select_surface_format(false, SrgbFallback::ForceFirst, &formats);
+        assert_eq!(format.unwrap(), wgpu::TextureFormat::Bgra8Unorm);
+    }
+
+    #[test]
+    fn select_surface_format_prefers_hdr_if_requested() {
+        let formats = [wgpu::TextureFormat::Bgra8UnormSrgb, HDR_FORMAT];
+        let format =
+            TestCanvas::select_surface_format(true, SrgbFallback::WarnAndFallback, &formats);
+        assert_eq!(format.unwrap(), HDR_FORMAT);
+
+        let formats = [wgpu::TextureFormat::Bgra8UnormSrgb];
+        let format =
+            TestCanvas::select_surface_format(true, SrgbFallback::WarnAndFallback, &formats);
+        assert_eq!(format.unwrap(), wgpu::TextureFormat::Bgra8UnormSrgb);
+    }
+
+    #[test]
+    fn select_surface_format_fails_without_formats() {
+        let format = TestCanvas::select_surface_format(false, SrgbFallback::WarnAndFallback, &[]);
+        assert!(format.is_err());
+    }
+
+    #[test]
+    fn select_present_mode_falls_back_to_first_supported() {
+        let modes = [wgpu::PresentMode::Fifo, wgpu::PresentMode::Mailbox];
+
+        let mode = TestCanvas::select_present_mode(wgpu::PresentMode::Mailbox, &modes);
+        assert_eq!(mode.unwrap(), wgpu::PresentMode::Mailbox);
+
+        let mode = TestCanvas::select_present_mode(wgpu::PresentMode::Immediate, &modes);
+        assert_eq!(mode.unwrap(), wgpu::PresentMode::Fifo);
+
+        assert!(TestCanvas::select_present_mode(wgpu::PresentMode::Fifo, &[]).is_err());
+    }
+
+    #[test]
+    fn select_alpha_mode_blends_transparent_windows() {
+        let modes = [
+            wgpu::CompositeAlphaMode::Opaque,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+            wgpu::CompositeAlphaMode::PreMultiplied,
+        ];
+
+        assert_eq!(
+            TestCanvas::select_alpha_mode(false, &modes),
+            wgpu::CompositeAlphaMode::Opaque
+        );
+        assert_eq!(
+            TestCanvas::select_alpha_mode(true, &modes),
+            wgpu::CompositeAlphaMode::PreMultiplied
+        );
+        assert_eq!(
+            TestCanvas::select_alpha_mode(true, &modes[..2]),
+            wgpu::CompositeAlphaMode::PostMultiplied
+        );
+        assert_eq!(
+            TestCanvas::select_alpha_mode(true, &modes[..1]),
+            wgpu::CompositeAlphaMode::Opaque
+        );
+    }
+
+    #[test]
+    fn stop_handler_stops_the_handler() {
+        let mut handler = TestHandler::default();
+        let mut profiler = FrameProfiler::new(FrameProfiling::Disabled).unwrap();
+
+        stop_handler(&mut handler, &mut profiler);
+        assert!(handler.stopped);
+    }
+
+    #[test]
+    fn stop_handler_writes_frame_timings() {
+        let path = std::env::temp_dir().join("wgpu-prog-stop-handler-timings.csv");
+        let mut handler = TestHandler::default();
+        let mut profiler = FrameProfiler::new(FrameProfiling::Csv(path.clone())).unwrap();
+        profiler.record(FrameTiming {
+            frame_index: 0,
+            cpu_time: Duration::from_millis(2),
+            gpu_time: Some(Duration::from_millis(1)),
+        });
+
+        stop_handler(&mut handler, &mut profiler);
+        assert!(handler.stopped);
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(csv, "frame_index,cpu_time_ms,gpu_time_ms\n0,2.000,1.000\n");
+    }
+
+    #[test]
+    fn deliver_input_passes_key_repeats_to_handler() {
+        let mut handler = TestHandler::default();
+        let physical = PhysicalKey::Code(KeyCode::KeyA);
+        for (pressed, repeat) in [(true, false), (true, true), (true, true), (false, false)] {
+            deliver_input(
+                &mut handler,
+                InputEvent::Keyboard {
+                    key: winit::keyboard::Key::Character("a".into()),
+                    physical,
+                    pressed,
+                    repeat,
+                    modifiers: ModifiersState::empty(),
+                },
+            );
+        }
+
+        assert_eq!(
+            handler.keys,
+            vec![
+                (physical, true, false),
+                (physical, true, true),
+                (physical, true, true),
+                (physical, false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_clear_values_validates_depth_config() {
+        let depth = DepthConfig::reverse_z(wgpu::TextureFormat::Depth32Float).unwrap();
+        let options = CanvasOptions {
+            depth: Some(depth),
+            ..CanvasOptions::default()
+        };
+        assert!(check_clear_values(&options).is_ok());
+
+        let options = CanvasOptions {
+            depth: Some(DepthConfig {
+                clear_value: 2.0,
+                ..depth
+            }),
+            ..CanvasOptions::default()
+        };
+        assert!(check_clear_values(&options).is_err());
+
+        let options = CanvasOptions {
+            depth: Some(DepthConfig {
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                ..depth
+            }),
+            ..CanvasOptions::default()
+        };
+        assert!(check_clear_values(&options).is_err());
+    }
 }
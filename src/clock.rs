@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+
+/// The source of the time the canvas is driven by, e.g., a deterministic clock for tests or a
+/// clock synchronized to audio or video playback. Only the differences between two points in
+/// time are used, so the origin of the returned time is arbitrary.
+pub trait TimeSource {
+    /// Returns the current time relative to an arbitrary, but fixed origin. The returned time
+    /// must never decrease.
+    fn now(&self) -> Duration;
+}
+
+/// The default time source, which is a monotonic real-time clock.
+#[derive(Clone, Copy, Debug)]
+pub struct MonotonicClock {
+    origin: Instant,
+}
+
+impl MonotonicClock {
+    /// Creates a new clock starting at zero.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for MonotonicClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
@@ -0,0 +1,340 @@
+use crate::control::CanvasControl;
+
+use std::path::Path;
+
+use crate::{
+    adapter::AdapterDetails, depth::DepthConfig, error::Result, profiling::FrameStats, shader,
+};
+
+#[cfg(feature = "hot-reload")]
+use crate::hot_reload::ShaderWatcher;
+#[cfg(feature = "text")]
+use crate::text::TextOverlay;
+
+/// The properties of the frame the handler renders into, which the color and depth targets of
+/// its pipelines have to match, see `RenderContext::surface_info`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SurfaceInfo {
+    /// The format of the color target, which is the sRGB format selected for the surface
+    /// unless configured otherwise in `CanvasOptions`.
+    pub format: wgpu::TextureFormat,
+    /// The width of the color target in physical pixels.
+    pub width: u32,
+    /// The height of the color target in physical pixels.
+    pub height: u32,
+    /// The number of samples per pixel, i.e., the `multisample.count` of the pipelines.
+    pub sample_count: u32,
+    /// The format of the depth buffer, if any.
+    pub depth_format: Option<wgpu::TextureFormat>,
+}
+
+/// The context for rendering, which gives the handler access to the GPU and the canvas.
+/// All data is accessed through methods, so new data can be added without breaking handlers.
+pub struct RenderContext<'a> {
+    device: &'a wgpu::Device,
+    queue: &'a wgpu::Queue,
+    control: &'a CanvasControl,
+    surface_config: &'a wgpu::SurfaceConfiguration,
+    size: (u32, u32),
+    scene_size: Option<(u32, u32)>,
+    frame_index: u64,
+    delta_time: f32,
+    interpolation_alpha: f32,
+    frame_stats: FrameStats,
+    depth_view: Option<&'a wgpu::TextureView>,
+    #[cfg(feature = "text")]
+    text_overlay: Option<&'a TextOverlay>,
+    #[cfg(feature = "hot-reload")]
+    shader_watcher: Option<&'a ShaderWatcher>,
+}
+
+impl<'a> RenderContext<'a> {
+    /// Creates a new render context.
+    ///
+    /// # Arguments
+    /// * `device` - The device used for rendering.
+    /// * `queue` - The command queue of the device.
+    /// * `control` - The control handle of the canvas.
+    /// * `surface_config` - The configuration of the surface.
+    /// * `size` - The width and height of the texture the handler renders into.
+    /// * `frame_index` - The index of the current frame.
+    pub(crate) fn new(
+        device: &'a wgpu::Device,
+        queue: &'a wgpu::Queue,
+        control: &'a CanvasControl,
+        surface_config: &'a wgpu::SurfaceConfiguration,
+        size: (u32, u32),
+        frame_index: u64,
+    ) -> Self {
+        Self {
+            device,
+            queue,
+            control,
+            surface_config,
+            size,
+            scene_size: None,
+            frame_index,
+            delta_time: 0.0,
+            interpolation_alpha: 1.0,
+            frame_stats: FrameStats {
+                frame_index,
+                ..FrameStats::default()
+            },
+            depth_view: None,
+            #[cfg(feature = "text")]
+            text_overlay: None,
+            #[cfg(feature = "hot-reload")]
+            shader_watcher: None,
+        }
+    }
+
+    /// Sets the overlay the texts of `draw_text` are queued in.
+    ///
+    /// # Arguments
+    /// * `text_overlay` - The text overlay of the canvas.
+    #[cfg(feature = "text")]
+    pub(crate) fn with_text_overlay(mut self, text_overlay: &'a TextOverlay) -> Self {
+        self.text_overlay = Some(text_overlay);
+        self
+    }
+
+    /// Sets the watcher the shaders of `load_shader` are registered with.
+    ///
+    /// # Arguments
+    /// * `shader_watcher` - The shader watcher of the canvas.
+    #[cfg(feature = "hot-reload")]
+    pub(crate) fn with_shader_watcher(mut self, shader_watcher: &'a ShaderWatcher) -> Self {
+        self.shader_watcher = Some(shader_watcher);
+        self
+    }
+
+    /// Returns the device used for rendering.
+    pub fn device(&self) -> &'a wgpu::Device {
+        self.device
+    }
+
+    /// Returns the command queue of the device.
+    pub fn queue(&self) -> &'a wgpu::Queue {
+        self.queue
+    }
+
+    /// Returns the details of the adapter used for rendering, e.g., for choosing the quality
+    /// of the effects in `EventHandler::setup` depending on the GPU.
+    pub fn adapter_details(&self) -> AdapterDetails {
+        self.control.adapter_details()
+    }
+
+    /// Returns the control handle of the canvas, which can be cloned and stored for later use.
+    pub fn control(&self) -> &'a CanvasControl {
+        self.control
+    }
+
+    /// Returns the format of the texture the handler renders into.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.surface_config.format
+    }
+
+    /// Returns the configuration the surface has been negotiated with, i.e., the format, the
+    /// present mode, the alpha mode and the size of the surface. In the scaled modes and while
+    /// the contents are preserved, the handler renders into an offscreen texture with the same
+    /// format, but possibly a different size, see `size`.
+    pub fn surface_config(&self) -> &'a wgpu::SurfaceConfiguration {
+        self.surface_config
+    }
+
+    /// Returns the width and height of the texture the handler renders into in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the format, the size, the sample count and the depth format of the frame the
+    /// handler renders into at once, e.g., for creating the pipelines in
+    /// `EventHandler::setup`.
+    pub fn surface_info(&self) -> SurfaceInfo {
+        SurfaceInfo {
+            format: self.format(),
+            width: self.size.0,
+            height: self.size.1,
+            sample_count: self.sample_count(),
+            depth_format: self.depth_format(),
+        }
+    }
+
+    /// Returns the width and height of the scene texture in pixels if a scene resolution is
+    /// set, see `CanvasControl::set_scene_resolution`.
+    pub fn scene_size(&self) -> Option<(u32, u32)> {
+        self.scene_size
+    }
+
+    /// Sets the size of the scene texture.
+    ///
+    /// # Arguments
+    /// * `scene_size` - The width and height of the scene texture, if any.
+    pub(crate) fn with_scene_size(mut self, scene_size: Option<(u32, u32)>) -> Self {
+        self.scene_size = scene_size;
+        self
+    }
+
+    /// Returns the index of the current frame, starting at 0 for the first frame.
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// Returns the time between the start of the previous and the current frame in seconds,
+    /// e.g., for animations independent of the frame rate. The time is read from
+    /// `CanvasOptions::time_source`. The first frame and the first frame after a pause report
+    /// zero. Callbacks outside of the frame report the delta time of the latest frame.
+    pub fn delta_time(&self) -> f32 {
+        self.delta_time
+    }
+
+    /// Sets the time since the previous frame.
+    ///
+    /// # Arguments
+    /// * `delta_time` - The time between the previous and the current frame in seconds.
+    pub(crate) fn with_delta_time(mut self, delta_time: f32) -> Self {
+        self.delta_time = delta_time;
+        self
+    }
+
+    /// Returns the fraction in [0, 1) of a fixed time step the current frame lies between the
+    /// last and the next call of `EventHandler::update`, e.g., for interpolating between the
+    /// previous and the current state of the simulation, see `CanvasOptions::fixed_timestep`.
+    /// Returns 1.0 if the updates don't run at a fixed rate, i.e., the current state is drawn.
+    pub fn interpolation_alpha(&self) -> f32 {
+        self.interpolation_alpha
+    }
+
+    /// Sets the interpolation factor between the last and the next fixed update.
+    ///
+    /// # Arguments
+    /// * `interpolation_alpha` - The fraction of the fixed time step since the last update.
+    pub(crate) fn with_interpolation_alpha(mut self, interpolation_alpha: f32) -> Self {
+        self.interpolation_alpha = interpolation_alpha;
+        self
+    }
+
+    /// Returns the frame rate and the frame time averaged over the recent frames, e.g., for an
+    /// on-screen FPS counter, see `CanvasOptions::frame_stats_window`. Callbacks outside of the
+    /// frame report the statistics as of the latest frame. In headless mode, only the frame
+    /// index is set.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats
+    }
+
+    /// Sets the statistics of the recent frames.
+    ///
+    /// # Arguments
+    /// * `frame_stats` - The averaged frame statistics.
+    pub(crate) fn with_frame_stats(mut self, frame_stats: FrameStats) -> Self {
+        self.frame_stats = frame_stats;
+        self
+    }
+
+    /// Returns the configuration of the depth buffer, see `CanvasOptions::depth`, e.g., for
+    /// creating the depth state of the pipelines with `DepthConfig::depth_stencil_state`, which
+    /// matches the compare function to the clear value. Returns `None` if the canvas has no
+    /// depth buffer.
+    pub fn depth_config(&self) -> Option<DepthConfig> {
+        self.control.depth_config()
+    }
+
+    /// Returns the format of the depth buffer, see `CanvasOptions::depth`. Returns `None` if the
+    /// canvas has no depth buffer.
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.control.depth_format()
+    }
+
+    /// Returns the scale factor of the monitor the window is on, e.g., for scaling fonts and
+    /// layouts in `EventHandler::setup`. Changes are reported by
+    /// `EventHandler::scale_factor_changed`.
+    pub fn scale_factor(&self) -> f64 {
+        self.control.scale_factor()
+    }
+
+    /// Returns the number of samples per pixel of the render pass of `next_frame`, see
+    /// `CanvasOptions::sample_count`, which is the `multisample.count` the pipelines drawing
+    /// into it must use.
+    pub fn sample_count(&self) -> u32 {
+        self.control.sample_count()
+    }
+
+    /// Returns the view of the depth buffer during the frame, e.g., for attaching it to the
+    /// passes of `EventHandler::on_frame`. Returns `None` outside of the frame or if the canvas
+    /// has no depth buffer.
+    pub fn depth_view(&self) -> Option<&'a wgpu::TextureView> {
+        self.depth_view
+    }
+
+    /// Sets the view of the depth buffer.
+    ///
+    /// # Arguments
+    /// * `depth_view` - The view of the depth buffer, if any.
+    pub(crate) fn with_depth_view(mut self, depth_view: Option<&'a wgpu::TextureView>) -> Self {
+        self.depth_view = depth_view;
+        self
+    }
+
+    /// Blocks until the GPU has finished all submitted work, e.g., before destroying or
+    /// replacing buffers and textures still used by submitted commands, or before a
+    /// synchronous readback. As this stalls the CPU until the GPU is idle, it should be used
+    /// sparingly and not in every frame.
+    pub fn wait_for_gpu_idle(&self) {
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    /// Loads the WGSL shader at the given path and compiles it into a shader module.
+    /// If the `hot-reload` feature is enabled, the file is watched and recompiled when it is
+    /// modified, which is signaled by `EventHandler::shaders_reloaded`. Loading the shader again
+    /// in this callback returns the recompiled module.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the WGSL file.
+    pub fn load_shader<P: AsRef<Path>>(&self, path: P) -> Result<wgpu::ShaderModule> {
+        #[cfg(feature = "hot-reload")]
+        if let Some(shader_watcher) = self.shader_watcher {
+            return shader_watcher.load(self.device, path.as_ref());
+        }
+
+        shader::load_shader(self.device, path.as_ref())
+    }
+
+    /// Creates the vertex shader for drawing a full-screen triangle, so only a fragment shader
+    /// has to be written. See `shader::create_fullscreen_vertex_shader` for the interface of
+    /// the shader. The module should be created once, e.g., in `setup`.
+    pub fn fullscreen_vertex_shader(&self) -> wgpu::ShaderModule {
+        shader::create_fullscreen_vertex_shader(self.device)
+    }
+
+    /// Draws a full-screen triangle with the given pipeline, whose vertex stage is the
+    /// full-screen vertex shader, e.g., in `next_frame`.
+    ///
+    /// # Arguments
+    /// * `pass` - The render pass to draw into.
+    /// * `pipeline` - The pipeline created with `fullscreen_vertex_shader`.
+    pub fn draw_fullscreen<'p>(
+        &self,
+        pass: &mut wgpu::RenderPass<'p>,
+        pipeline: &'p wgpu::RenderPipeline,
+    ) {
+        shader::draw_fullscreen(pass, pipeline);
+    }
+
+    /// Draws the given text on top of the frame, e.g., for debug overlays and simple labels.
+    /// The text is rendered with the bundled Fira Mono font after the handler has rendered its
+    /// content, i.e., text drawn in `on_frame` or `next_frame` appears in the current frame and
+    /// text drawn in `setup` appears in the first frame.
+    ///
+    /// # Arguments
+    /// * `text` - The text, which may span multiple lines.
+    /// * `position` - The top-left corner of the text in pixels of the texture the handler
+    ///   renders into.
+    /// * `size` - The font size in pixels.
+    /// * `color` - The color of the text.
+    #[cfg(feature = "text")]
+    pub fn draw_text(&self, text: &str, position: (f32, f32), size: f32, color: wgpu::Color) {
+        if let Some(text_overlay) = self.text_overlay {
+            text_overlay.queue(text, position, size, color);
+        }
+    }
+}
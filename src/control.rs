@@ -0,0 +1,723 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    sync::mpsc,
+    thread::{self, ThreadId},
+    time::Duration,
+};
+
+pub use winit::raw_window_handle;
+pub use winit::window::CursorGrabMode;
+
+use raw_window_handle::{DisplayHandle, HasDisplayHandle, HasWindowHandle, WindowHandle};
+use winit::{
+    event_loop::EventLoopProxy,
+    window::{Fullscreen, Window},
+};
+
+#[cfg(feature = "assets")]
+use crate::assets::{Asset, AssetId, AssetLoader};
+use crate::{
+    adapter::{AdapterClass, AdapterDetails},
+    canvas::{CanvasOptions, HDR_FORMAT},
+    clock::TimeSource,
+    depth::DepthConfig,
+    error::{Error, Result},
+    input::{InputCaptureId, InputEvent, InputState},
+    memory::GpuMemoryReport,
+    monitor::{MonitorArea, MonitorInfo},
+    scaling::Viewport,
+};
+
+/// A request for opening or closing the mirror window, which is handled by the event loop.
+pub(crate) enum MirrorRequest {
+    /// Open the mirror window with the given title.
+    Open(String),
+    /// Close the mirror window.
+    Close,
+}
+
+/// The user events sent to the event loop of the canvas.
+pub(crate) enum CanvasEvent {
+    /// Render and present exactly one frame and send back the result.
+    RenderNow(mpsc::Sender<Result<()>>),
+    /// Dispatch the synthetic input events as if they were received from the window.
+    InjectInput(Vec<InputEvent>),
+    /// Replace the tracked input state without dispatching any events.
+    RestoreInputState(InputState),
+    /// An asset has been decoded by a worker thread.
+    #[cfg(feature = "assets")]
+    AssetLoaded(AssetId, Result<Asset>),
+}
+
+/// A handle for controlling the canvas from other threads, e.g., a tool that drives the canvas
+/// from external input. Obtained through `CanvasControl::controller`.
+#[derive(Clone)]
+pub struct CanvasController {
+    /// The proxy of the event loop, which doesn't exist in headless mode.
+    proxy: Option<EventLoopProxy<CanvasEvent>>,
+    event_loop_thread: ThreadId,
+}
+
+impl CanvasController {
+    /// Creates a new controller for the event loop running on the current thread.
+    ///
+    /// # Arguments
+    /// * `proxy` - The proxy for sending events to the event loop.
+    pub(crate) fn new(proxy: EventLoopProxy<CanvasEvent>) -> Self {
+        Self {
+            proxy: Some(proxy),
+            event_loop_thread: thread::current().id(),
+        }
+    }
+
+    /// Creates a controller for a headless canvas, which has no event loop. All requests
+    /// return an error.
+    pub(crate) fn headless() -> Self {
+        Self {
+            proxy: None,
+            event_loop_thread: thread::current().id(),
+        }
+    }
+
+    /// Returns the proxy for sending events to the event loop, if any.
+    #[cfg(feature = "assets")]
+    pub(crate) fn proxy(&self) -> Option<EventLoopProxy<CanvasEvent>> {
+        self.proxy.clone()
+    }
+
+    /// Sends the given event to the event loop.
+    ///
+    /// # Arguments
+    /// * `event` - The event to send.
+    fn send(&self, event: CanvasEvent) -> Result<()> {
+        self.proxy
+            .as_ref()
+            .ok_or_else(|| Error::Internal("The canvas has no event loop".to_string()))?
+            .send_event(event)
+            .map_err(|_| Error::Internal("The event loop has been closed".to_string()))
+    }
+
+    /// Dispatches the given synthetic input events to the handler in order, as if they were
+    /// received from the window, e.g., for replaying recorded input in tests and demos. The
+    /// coordinates are interpreted like the ones reported to the handler, see
+    /// `CanvasOptions::cursor_origin`. The tracked input state is updated accordingly.
+    ///
+    /// # Arguments
+    /// * `events` - The input events to dispatch.
+    pub fn inject_input(&self, events: Vec<InputEvent>) -> Result<()> {
+        self.send(CanvasEvent::InjectInput(events))
+    }
+
+    /// Replaces the tracked input state, e.g., with a state recorded by
+    /// `CanvasControl::input_state` before replaying input. No events are dispatched to the
+    /// handler.
+    ///
+    /// # Arguments
+    /// * `state` - The input state to restore.
+    pub fn restore_input_state(&self, state: InputState) -> Result<()> {
+        self.send(CanvasEvent::RestoreInputState(state))
+    }
+
+    /// Renders and presents exactly one frame on the event loop thread and blocks until the
+    /// frame is done. Returns the error if rendering the frame failed.
+    ///
+    /// As this waits for the event loop, it must not be called from the event loop thread
+    /// itself, i.e., from within the callbacks of the event handler. Doing so returns an error.
+    pub fn render_now(&self) -> Result<()> {
+        if thread::current().id() == self.event_loop_thread {
+            return Err(Error::Internal(
+                "render_now must not be called from the event loop thread".to_string(),
+            ));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        self.send(CanvasEvent::RenderNow(sender))?;
+
+        receiver.recv().map_err(|_| {
+            Error::Internal("The event loop has been closed before rendering".to_string())
+        })?
+    }
+}
+
+/// The state shared between the canvas and all of its control handles.
+struct ControlState {
+    /// The window of the canvas, which doesn't exist in headless mode.
+    window: Option<Rc<Window>>,
+    instance: wgpu::Instance,
+    adapter_info: wgpu::AdapterInfo,
+    controller: CanvasController,
+    /// The source of the time the canvas is driven by.
+    time_source: Box<dyn TimeSource>,
+    /// The point in time the canvas has been started or the clock has been reset.
+    start: Cell<Duration>,
+    /// The stack of active exclusive input captures. The topmost one receives all input.
+    input_captures: RefCell<Vec<InputCaptureId>>,
+    /// The id for the next input capture.
+    next_input_capture: Cell<u64>,
+    /// True if the last frame has been presented on the surface.
+    last_frame_presented: Cell<bool>,
+    /// The rectangle of the surface the content of the handler is shown in.
+    content_viewport: Cell<Viewport>,
+    /// The pending request for opening or closing the mirror window.
+    mirror_request: RefCell<Option<MirrorRequest>>,
+    /// True if the mirror window is open.
+    mirror_open: Cell<bool>,
+    /// True if a screenshot has been requested.
+    screenshot_requested: Cell<bool>,
+    /// True if the handler requested to exit the event loop.
+    exit_requested: Cell<bool>,
+    /// True if a redraw has been requested in the on-demand render mode.
+    redraw_requested: Cell<bool>,
+    /// True if rendering and input are paused.
+    paused: Cell<bool>,
+    /// The current configuration of the surface.
+    surface_config: RefCell<wgpu::SurfaceConfiguration>,
+    /// The configuration requested by the handler, which is applied before the next frame.
+    requested_surface_config: RefCell<Option<wgpu::SurfaceConfiguration>>,
+    /// The resolution the scene is rendered at, if decoupled from the overlay.
+    scene_resolution: Cell<Option<(u32, u32)>>,
+    /// The configuration of the depth buffer, if any.
+    depth: Option<DepthConfig>,
+    /// The number of samples per pixel of the default render pass.
+    sample_count: Cell<u32>,
+    /// The input state tracked by the canvas.
+    input_state: RefCell<InputState>,
+    /// The pool of worker threads decoding assets.
+    #[cfg(feature = "assets")]
+    asset_loader: AssetLoader,
+}
+
+/// A handle to the running canvas, which is handed to the event handler during setup.
+/// The handle is cheap to clone and can be stored by the handler for later use.
+#[derive(Clone)]
+pub struct CanvasControl {
+    state: Rc<ControlState>,
+}
+
+impl CanvasControl {
+    /// Creates a new control handle for the canvas.
+    ///
+    /// # Arguments
+    /// * `window` - The window of the canvas, or `None` in headless mode.
+    /// * `instance` - The wgpu instance the device has been created with.
+    /// * `adapter_info` - The information about the adapter used for rendering.
+    /// * `surface_config` - The initial configuration of the surface.
+    /// * `options` - The options the canvas has been created with.
+    /// * `time_source` - The source of the time the canvas is driven by.
+    /// * `controller` - The controller for accessing the canvas from other threads.
+    pub(crate) fn new(
+        window: Option<Rc<Window>>,
+        instance: wgpu::Instance,
+        adapter_info: wgpu::AdapterInfo,
+        surface_config: wgpu::SurfaceConfiguration,
+        options: &CanvasOptions,
+        time_source: Box<dyn TimeSource>,
+        controller: CanvasController,
+    ) -> Result<Self> {
+        let start = time_source.now();
+
+        #[cfg(feature = "assets")]
+        let asset_loader = AssetLoader::new(options.asset_worker_threads, controller.proxy())?;
+
+        Ok(Self {
+            state: Rc::new(ControlState {
+                window,
+                instance,
+                adapter_info,
+                controller,
+                time_source,
+                start: Cell::new(start),
+                input_captures: RefCell::new(Vec::new()),
+                next_input_capture: Cell::new(0),
+                last_frame_presented: Cell::new(false),
+                content_viewport: Cell::new(Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: 0.0,
+                    height: 0.0,
+                }),
+                mirror_request: RefCell::new(None),
+                mirror_open: Cell::new(false),
+                screenshot_requested: Cell::new(false),
+                exit_requested: Cell::new(false),
+                // the first frame is always rendered
+                redraw_requested: Cell::new(true),
+                paused: Cell::new(false),
+                surface_config: RefCell::new(surface_config),
+                requested_surface_config: RefCell::new(None),
+                scene_resolution: Cell::new(options.scene_resolution),
+                depth: options.depth,
+                sample_count: Cell::new(1),
+                input_state: RefCell::new(InputState::default()),
+                #[cfg(feature = "assets")]
+                asset_loader,
+            }),
+        })
+    }
+
+    /// Returns the configuration of the depth buffer, see `CanvasOptions::depth`.
+    pub fn depth_config(&self) -> Option<DepthConfig> {
+        self.state.depth
+    }
+
+    /// Returns the format of the depth buffer, see `CanvasOptions::depth`.
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.state.depth.map(|depth| depth.format)
+    }
+
+    /// Returns the number of samples per pixel of the render pass of `next_frame`, see
+    /// `CanvasOptions::sample_count`.
+    pub fn sample_count(&self) -> u32 {
+        self.state.sample_count.get()
+    }
+
+    /// Records the sample count the canvas actually uses.
+    ///
+    /// # Arguments
+    /// * `sample_count` - The supported sample count.
+    pub(crate) fn set_sample_count(&self, sample_count: u32) {
+        self.state.sample_count.set(sample_count);
+    }
+
+    /// Returns the backend of the adapter that is actually used for rendering.
+    pub fn backend(&self) -> wgpu::Backend {
+        self.state.adapter_info.backend
+    }
+
+    /// Returns the name of the backend that is actually used for rendering, e.g., "vulkan".
+    pub fn backend_name(&self) -> &'static str {
+        self.backend().to_str()
+    }
+
+    /// Returns the details of the adapter that is actually used for rendering, e.g., its name
+    /// for showing it in the UI.
+    pub fn adapter_details(&self) -> AdapterDetails {
+        AdapterDetails::of_adapter(&self.state.adapter_info)
+    }
+
+    /// Returns the class of the adapter that is actually used for rendering, e.g., for
+    /// checking whether the canvas runs on a real GPU or on a software rasterizer.
+    pub fn adapter_class(&self) -> AdapterClass {
+        AdapterClass::of_adapter(&self.state.adapter_info)
+    }
+
+    /// Returns the area of the monitor the window is currently on in physical pixels, e.g., for
+    /// positioning the window on the same screen. Returns `None` if the monitor can't be
+    /// determined, e.g., on headless systems or in headless mode.
+    ///
+    /// The area covers the whole monitor including task bars, docks and other system bars,
+    /// whose geometry isn't provided by winit. Use `CanvasOptions::fit_to_work_area` to let the
+    /// window manager size the window to the work area excluding them.
+    pub fn monitor_area(&self) -> Option<MonitorArea> {
+        self.state
+            .window
+            .as_ref()?
+            .current_monitor()
+            .map(|monitor| MonitorArea::of_monitor(&monitor))
+    }
+
+    /// Returns the window of the canvas or an error in headless mode.
+    fn window(&self) -> Result<&Window> {
+        self.state
+            .window
+            .as_deref()
+            .ok_or_else(|| Error::Internal("The canvas has no window".to_string()))
+    }
+
+    /// Returns the raw platform handle of the window, e.g., for embedding video playback or
+    /// opening native dialogs attached to the canvas. The handle borrows the control handle, so
+    /// it can't outlive the window in safe code. Any native code receiving the raw handle must
+    /// not keep using it after the canvas has been closed, must not destroy the window and
+    /// must not render into it, as the surface of the canvas is presented into the window.
+    /// Returns an error in headless mode.
+    pub fn window_handle(&self) -> Result<WindowHandle<'_>> {
+        self.window()?
+            .window_handle()
+            .map_err(|e| Error::GraphicsAPI(format!("Failed to get the window handle: {}", e)))
+    }
+
+    /// Returns the raw platform handle of the display connection the window belongs to, e.g.,
+    /// the X11 or Wayland display. The same contract as for `window_handle` applies.
+    pub fn display_handle(&self) -> Result<DisplayHandle<'_>> {
+        self.window()?
+            .display_handle()
+            .map_err(|e| Error::GraphicsAPI(format!("Failed to get the display handle: {}", e)))
+    }
+
+    /// Returns true if the window has decorations, i.e., a title bar and borders drawn by the
+    /// window manager. Returns false in headless mode.
+    pub fn is_decorated(&self) -> bool {
+        self.state
+            .window
+            .as_ref()
+            .is_some_and(|window| window.is_decorated())
+    }
+
+    /// Shows or hides the decorations of the window, e.g., for applications drawing their own
+    /// title bar. The size of the surface may change as a result, which is handled like any
+    /// other resize of the window. Does nothing in headless mode.
+    ///
+    /// # Arguments
+    /// * `decorations` - True for showing the decorations and false for a borderless window.
+    pub fn set_decorations(&self, decorations: bool) {
+        if let Some(window) = &self.state.window {
+            window.set_decorations(decorations);
+        }
+    }
+
+    /// Shows or hides the cursor while it is over the window, e.g., for first-person camera
+    /// controls together with `set_cursor_grab`. Does nothing in headless mode.
+    ///
+    /// # Arguments
+    /// * `visible` - True for showing and false for hiding the cursor.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        if let Some(window) = &self.state.window {
+            window.set_cursor_visible(visible);
+        }
+    }
+
+    /// Grabs the cursor, e.g., for first-person camera controls. `Locked` keeps the cursor at
+    /// its position and `Confined` keeps it inside the window. As platforms only support one
+    /// of both, e.g., macOS only supports `Locked` and Windows only `Confined`, the other mode
+    /// is used as fallback. `None` releases the grab. Returns an error if the platform doesn't
+    /// support grabbing the cursor at all or in headless mode.
+    ///
+    /// # Arguments
+    /// * `mode` - The requested grab mode.
+    pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<()> {
+        let window = self.window()?;
+
+        let fallback = match mode {
+            CursorGrabMode::None => CursorGrabMode::None,
+            CursorGrabMode::Confined => CursorGrabMode::Locked,
+            CursorGrabMode::Locked => CursorGrabMode::Confined,
+        };
+
+        window
+            .set_cursor_grab(mode)
+            .or_else(|_| window.set_cursor_grab(fallback))
+            .map_err(|e| Error::GraphicsAPI(format!("Failed to grab the cursor: {}", e)))
+    }
+
+    /// Returns the scale factor of the monitor the window is on, i.e., the number of physical
+    /// pixels per logical pixel. Returns 1.0 in headless mode.
+    pub fn scale_factor(&self) -> f64 {
+        self.state
+            .window
+            .as_ref()
+            .map_or(1.0, |window| window.scale_factor())
+    }
+
+    /// Returns true if the window is in fullscreen mode. Returns false in headless mode.
+    pub fn is_fullscreen(&self) -> bool {
+        self.state
+            .window
+            .as_ref()
+            .is_some_and(|window| window.fullscreen().is_some())
+    }
+
+    /// Enters or leaves the borderless fullscreen mode on the monitor the window is currently
+    /// on. The surface is resized like for any other resize of the window, and the handler is
+    /// notified through `EventHandler::resize`. Does nothing in headless mode.
+    ///
+    /// # Arguments
+    /// * `fullscreen` - True for entering and false for leaving the fullscreen mode.
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if let Some(window) = &self.state.window {
+            window.set_fullscreen(fullscreen.then_some(Fullscreen::Borderless(None)));
+        }
+    }
+
+    /// Returns true if the surface has the HDR format, i.e., if HDR output has been requested
+    /// by `CanvasOptions::hdr` and is supported by the surface. Colors written by the handler
+    /// are linear and may exceed 1.0 in this case.
+    pub fn is_hdr(&self) -> bool {
+        self.state.surface_config.borrow().format == HDR_FORMAT
+    }
+
+    /// Returns the current configuration of the surface, e.g., for modifying several fields
+    /// and applying them at once with `apply_surface_config`.
+    pub fn surface_config(&self) -> wgpu::SurfaceConfiguration {
+        self.state.surface_config.borrow().clone()
+    }
+
+    /// Reconfigures the surface with the given configuration before the next frame, which
+    /// changes multiple fields, e.g., the format and the present mode, with a single
+    /// reconfiguration. The width and height are ignored, since the size of the surface is
+    /// determined by the window. If the configuration isn't supported by the surface, it is
+    /// rejected and the error is reported to `EventHandler::render_error`.
+    ///
+    /// # Arguments
+    /// * `config` - The new configuration of the surface.
+    pub fn apply_surface_config(&self, config: wgpu::SurfaceConfiguration) {
+        *self.state.requested_surface_config.borrow_mut() = Some(config);
+    }
+
+    /// Takes the configuration requested by the handler, if any.
+    pub(crate) fn take_requested_surface_config(&self) -> Option<wgpu::SurfaceConfiguration> {
+        self.state.requested_surface_config.borrow_mut().take()
+    }
+
+    /// Records the current configuration of the surface.
+    pub(crate) fn set_surface_config(&self, config: &wgpu::SurfaceConfiguration) {
+        *self.state.surface_config.borrow_mut() = config.clone();
+    }
+
+    /// Returns the resolution the scene is rendered at, if decoupled from the overlay.
+    pub fn scene_resolution(&self) -> Option<(u32, u32)> {
+        self.state.scene_resolution.get()
+    }
+
+    /// Sets the resolution the scene is rendered at, e.g., lower than the surface for saving
+    /// GPU time in a 3D view while the UI stays sharp. The scene texture is recreated before
+    /// the next frame. See `EventHandler::on_overlay`. Passing `None` renders the scene at the
+    /// native resolution again.
+    ///
+    /// # Arguments
+    /// * `resolution` - The width and height of the scene, which must be non-zero.
+    pub fn set_scene_resolution(&self, resolution: Option<(u32, u32)>) -> Result<()> {
+        check_scene_resolution(resolution)?;
+        self.state.scene_resolution.set(resolution);
+
+        Ok(())
+    }
+
+    /// Returns the properties of all monitors connected to the system, e.g., for letting the
+    /// user select a monitor. The monitors can be queried from `setup` on, i.e., before the
+    /// first frame. Returns an empty list if the platform doesn't report any monitors, e.g., on
+    /// headless systems or in headless mode.
+    pub fn monitors(&self) -> Vec<MonitorInfo> {
+        let window = match &self.state.window {
+            Some(window) => window,
+            None => return Vec::new(),
+        };
+        let primary = window.primary_monitor();
+
+        window
+            .available_monitors()
+            .map(|monitor| MonitorInfo::of_monitor(&monitor, primary.as_ref()))
+            .collect()
+    }
+
+    /// Opens a secondary window which mirrors the content of the canvas, e.g., for showing it
+    /// on a projector while controlling it from the main window. The content is rendered only
+    /// once and drawn onto both windows, keeping its aspect ratio in the mirror window. Input
+    /// of the mirror window is ignored. The window is opened before the next frame. If a mirror
+    /// window is already open, only its title is changed.
+    ///
+    /// # Arguments
+    /// * `title` - The title of the mirror window.
+    pub fn open_mirror(&self, title: &str) {
+        *self.state.mirror_request.borrow_mut() = Some(MirrorRequest::Open(title.to_string()));
+    }
+
+    /// Closes the mirror window, if any. The window is closed before the next frame.
+    pub fn close_mirror(&self) {
+        *self.state.mirror_request.borrow_mut() = Some(MirrorRequest::Close);
+    }
+
+    /// Returns true if the mirror window is open.
+    pub fn is_mirror_open(&self) -> bool {
+        self.state.mirror_open.get()
+    }
+
+    /// Takes the pending request for opening or closing the mirror window.
+    pub(crate) fn take_mirror_request(&self) -> Option<MirrorRequest> {
+        self.state.mirror_request.borrow_mut().take()
+    }
+
+    /// Records whether the mirror window is open.
+    pub(crate) fn set_mirror_open(&self, open: bool) {
+        self.state.mirror_open.set(open);
+    }
+
+    /// Requests to exit the event loop, e.g., when Escape has been pressed or an animation
+    /// has finished. Unlike closing the window, the request can't be vetoed by
+    /// `EventHandler::close_requested`. The loop exits once the current events have been
+    /// handled, after `EventHandler::stop` has been called.
+    pub fn request_exit(&self) {
+        self.state.exit_requested.set(true);
+    }
+
+    /// Takes the pending exit request, if any.
+    pub(crate) fn take_exit_request(&self) -> bool {
+        self.state.exit_requested.replace(false)
+    }
+
+    /// Requests a redraw in the `RenderMode::OnDemand` mode, e.g., after input changed the
+    /// content. Multiple requests before the next frame result in a single frame. Has no
+    /// effect in the continuous mode, where every frame is rendered anyway.
+    pub fn request_redraw(&self) {
+        self.state.redraw_requested.set(true);
+    }
+
+    /// Takes the pending redraw request, if any.
+    pub(crate) fn take_redraw_request(&self) -> bool {
+        self.state.redraw_requested.replace(false)
+    }
+
+    /// Requests a screenshot of the next frame, e.g., for bug reports or screen recordings.
+    /// The surface texture is copied after the frame has been rendered and handed to
+    /// `EventHandler::screenshot_ready` once it has been read back, which doesn't block the
+    /// rendering. Errors, e.g., if the surface doesn't support copying its texture or has an HDR
+    /// format, are reported to `EventHandler::render_error`. Multiple requests before the
+    /// screenshot has been taken result in a single screenshot.
+    pub fn request_screenshot(&self) {
+        self.state.screenshot_requested.set(true);
+    }
+
+    /// Takes the pending screenshot request, if any.
+    pub(crate) fn take_screenshot_request(&self) -> bool {
+        self.state.screenshot_requested.replace(false)
+    }
+
+    /// Returns the wgpu instance the device has been created with.
+    pub(crate) fn instance(&self) -> &wgpu::Instance {
+        &self.state.instance
+    }
+
+    /// Returns an estimate of the GPU resources allocated by the device, which can be polled
+    /// regularly to watch for leaks. Returns `None` if the `memory-report` feature is disabled
+    /// or the backend doesn't support reports, e.g., on the web.
+    pub fn gpu_memory_report(&self) -> Option<GpuMemoryReport> {
+        GpuMemoryReport::generate(&self.state.instance, self.backend())
+    }
+
+    /// Decodes an asset on a worker thread, e.g., an image or a mesh read from disk, so the
+    /// render loop stays responsive while loading large assets. The decoded asset, or the error
+    /// returned by the decoder, is handed to `EventHandler::asset_loaded` on the event loop
+    /// thread, where it can be uploaded to the GPU. Returns the id of the asset, which is
+    /// passed to the callback as well. In headless mode, there's no event loop the asset could
+    /// be delivered on, so it is dropped after decoding.
+    ///
+    /// # Arguments
+    /// * `decode` - The function decoding the asset, which is executed on a worker thread.
+    #[cfg(feature = "assets")]
+    pub fn load_asset<F, T>(&self, decode: F) -> AssetId
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.state.asset_loader.load(decode)
+    }
+
+    /// Returns a controller for the canvas, which can be sent to other threads.
+    pub fn controller(&self) -> CanvasController {
+        self.state.controller.clone()
+    }
+
+    /// Returns the time in seconds since the canvas has been started or the clock has been
+    /// reset. The value increases monotonically, e.g., for a global time uniform in shaders.
+    /// The time is read from `CanvasOptions::time_source`.
+    pub fn elapsed(&self) -> f32 {
+        self.now()
+            .saturating_sub(self.state.start.get())
+            .as_secs_f32()
+    }
+
+    /// Resets the clock returned by `elapsed` to zero.
+    pub fn reset_elapsed(&self) {
+        self.state.start.set(self.now());
+    }
+
+    /// Returns the current time of the time source the canvas is driven by, see
+    /// `CanvasOptions::time_source`.
+    pub fn now(&self) -> Duration {
+        self.state.time_source.now()
+    }
+
+    /// Returns true if the last frame has actually been presented on the surface and false if
+    /// it has been dropped, e.g., because the surface texture couldn't be acquired.
+    pub fn last_frame_presented(&self) -> bool {
+        self.state.last_frame_presented.get()
+    }
+
+    /// Records whether the last frame has been presented.
+    pub(crate) fn set_last_frame_presented(&self, presented: bool) {
+        self.state.last_frame_presented.set(presented);
+    }
+
+    /// Returns the rectangle of the surface in physical pixels the content of the handler is
+    /// shown in, e.g., for mapping cursor positions into the content in the letterboxed and
+    /// integer scaled modes. Covers the whole surface in the native mode.
+    pub fn content_viewport(&self) -> Viewport {
+        self.state.content_viewport.get()
+    }
+
+    /// Records the rectangle of the surface the content is shown in.
+    pub(crate) fn set_content_viewport(&self, viewport: Viewport) {
+        self.state.content_viewport.set(viewport);
+    }
+
+    /// Pauses the canvas, i.e., no frames are rendered and input events aren't delivered to
+    /// the handler until `resume` is called. The input received meanwhile is handled according
+    /// to `CanvasOptions::paused_input`. Frames requested with `CanvasController::render_now`
+    /// are still rendered.
+    pub fn pause(&self) {
+        self.state.paused.set(true);
+    }
+
+    /// Resumes rendering and the delivery of input events after `pause`.
+    pub fn resume(&self) {
+        self.state.paused.set(false);
+    }
+
+    /// Returns true if the canvas is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.get()
+    }
+
+    /// Returns the current input state, i.e., the held keys and mouse buttons, the cursor
+    /// position and the modifiers, e.g., for recording it before recording input events.
+    pub fn input_state(&self) -> InputState {
+        self.state.input_state.borrow().clone()
+    }
+
+    /// Updates the input state tracked by the canvas.
+    ///
+    /// # Arguments
+    /// * `state` - The current input state.
+    pub(crate) fn set_input_state(&self, state: InputState) {
+        *self.state.input_state.borrow_mut() = state;
+    }
+
+    /// Puts the canvas into an exclusive input mode, e.g., while dragging a gizmo. Until the
+    /// capture is released, all input events are delivered to `EventHandler::captured_input`
+    /// with the returned id instead of the regular input callbacks. Captures can be nested, in
+    /// which case the most recently pushed capture receives the input.
+    pub fn push_input_capture(&self) -> InputCaptureId {
+        let id = InputCaptureId(self.state.next_input_capture.get());
+        self.state.next_input_capture.set(id.0 + 1);
+        self.state.input_captures.borrow_mut().push(id);
+
+        id
+    }
+
+    /// Releases the given exclusive input capture. Does nothing if it was already released.
+    ///
+    /// # Arguments
+    /// * `id` - The id returned by `push_input_capture`.
+    pub fn release_input_capture(&self, id: InputCaptureId) {
+        self.state.input_captures.borrow_mut().retain(|c| *c != id);
+    }
+
+    /// Returns the exclusive input capture currently receiving all input, if any.
+    pub fn active_input_capture(&self) -> Option<InputCaptureId> {
+        self.state.input_captures.borrow().last().copied()
+    }
+}
+
+/// Checks that the given scene resolution is non-zero.
+///
+/// # Arguments
+/// * `resolution` - The width and height of the scene, if any.
+pub(crate) fn check_scene_resolution(resolution: Option<(u32, u32)>) -> Result<()> {
+    match resolution {
+        Some((width, height)) if width == 0 || height == 0 => Err(Error::Internal(format!(
+            "Invalid scene resolution {}x{}",
+            width, height
+        ))),
+        _ => Ok(()),
+    }
+}
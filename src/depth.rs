@@ -0,0 +1,106 @@
+use crate::error::{Error, Result};
+
+/// The depth configuration shared by the depth texture, its clear value and the depth state of
+/// the pipelines, which have to match each other. The depth buffer of the canvas is configured
+/// with `CanvasOptions::depth`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DepthConfig {
+    /// The format of the depth texture.
+    pub format: wgpu::TextureFormat,
+    /// The value the depth texture is cleared to, i.e., the depth of the far plane.
+    pub clear_value: f32,
+    /// The comparison passing fragments closer to the camera.
+    pub compare: wgpu::CompareFunction,
+}
+
+impl DepthConfig {
+    /// Creates the conventional configuration, where the near plane maps to depth 0 and the far
+    /// plane to depth 1, i.e., the texture is cleared to 1.0 and compared with `Less`.
+    ///
+    /// # Arguments
+    /// * `format` - The format of the depth texture, which must have a depth aspect.
+    pub fn standard(format: wgpu::TextureFormat) -> Result<Self> {
+        check_depth_format(format)?;
+
+        Ok(Self {
+            format,
+            clear_value: 1.0,
+            compare: wgpu::CompareFunction::Less,
+        })
+    }
+
+    /// Creates the reverse-Z configuration, where the near plane maps to depth 1 and the far
+    /// plane to depth 0, i.e., the texture is cleared to 0.0 and compared with `Greater`.
+    /// Together with a floating point format, this distributes the precision evenly over the
+    /// distance. The projection matrix has to map the near plane to 1 and the far plane to 0.
+    ///
+    /// # Arguments
+    /// * `format` - The format of the depth texture, which must be a floating point depth
+    ///   format, as reverse-Z doesn't improve the precision of normalized formats.
+    pub fn reverse_z(format: wgpu::TextureFormat) -> Result<Self> {
+        check_depth_format(format)?;
+
+        if !matches!(
+            format,
+            wgpu::TextureFormat::Depth32Float | wgpu::TextureFormat::Depth32FloatStencil8
+        ) {
+            return Err(Error::Internal(format!(
+                "Reverse-Z requires a floating point depth format, but {:?} was given",
+                format
+            )));
+        }
+
+        Ok(Self {
+            format,
+            clear_value: 0.0,
+            compare: wgpu::CompareFunction::Greater,
+        })
+    }
+
+    /// Returns true if this is a reverse-Z configuration.
+    pub fn is_reverse_z(&self) -> bool {
+        matches!(
+            self.compare,
+            wgpu::CompareFunction::Greater | wgpu::CompareFunction::GreaterEqual
+        )
+    }
+
+    /// Returns the depth state for a pipeline rendering into a depth texture with this
+    /// configuration.
+    ///
+    /// # Arguments
+    /// * `depth_write_enabled` - True if the pipeline writes the depth of its fragments.
+    pub fn depth_stencil_state(&self, depth_write_enabled: bool) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: self.format,
+            depth_write_enabled,
+            depth_compare: self.compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
+    }
+
+    /// Returns the operations clearing the depth texture at the start of a render pass and
+    /// storing the result.
+    pub fn clear_ops(&self) -> wgpu::Operations<f32> {
+        wgpu::Operations {
+            load: wgpu::LoadOp::Clear(self.clear_value),
+            store: wgpu::StoreOp::Store,
+        }
+    }
+}
+
+/// Checks that the given format can be used for a depth texture.
+///
+/// # Arguments
+/// * `format` - The format to check.
+pub(crate) fn check_depth_format(format: wgpu::TextureFormat) -> Result<()> {
+    if format.has_depth_aspect() {
+        Ok(())
+    } else {
+        Err(Error::Internal(format!(
+            "{:?} isn't a depth format",
+            format
+        )))
+    }
+}
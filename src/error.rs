@@ -3,7 +3,7 @@ use std::io;
 use wgpu::SurfaceError;
 
 quick_error! {
-    #[derive(Debug)]
+    #[derive(Clone, Debug)]
     pub enum Error {
         IO(err: String) {
             display("{}", err)
@@ -17,6 +17,12 @@ quick_error! {
         ContextLost(err: String) {
             display("{}", err)
         }
+        Outdated(err: String) {
+            display("{}", err)
+        }
+        Timeout(err: String) {
+            display("{}", err)
+        }
         OutOfMemory(err: String) {
             display("{}", err)
         }
@@ -33,11 +39,29 @@ impl From<SurfaceError> for Error {
     fn from(error: SurfaceError) -> Self {
         match error {
             SurfaceError::Lost => Error::ContextLost("Lost".to_string()),
+            SurfaceError::Outdated => Error::Outdated("Outdated".to_string()),
+            SurfaceError::Timeout => Error::Timeout("Timeout".to_string()),
             SurfaceError::OutOfMemory => Error::OutOfMemory("Out of memory".to_string()),
-            _ => Error::GraphicsAPI(format!("{}", error)),
         }
     }
 }
 
 /// The result type used in this crate.
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outdated_surface_maps_to_outdated_error() {
+        let error = Error::from(SurfaceError::Outdated);
+        assert!(matches!(error, Error::Outdated(_)));
+    }
+
+    #[test]
+    fn lost_surface_maps_to_context_lost_error() {
+        let error = Error::from(SurfaceError::Lost);
+        assert!(matches!(error, Error::ContextLost(_)));
+    }
+}
@@ -1,23 +1,192 @@
-use std::error::Error;
+use std::{error::Error, path::PathBuf};
 
-pub use winit::event::MouseButton;
-pub use winit::keyboard::Key;
+#[cfg(feature = "assets")]
+use crate::assets::{Asset, AssetId};
+
+pub use winit::event::{ElementState, MouseButton, TouchPhase};
+pub use winit::keyboard::{Key, KeyCode, ModifiersState, PhysicalKey};
+
+use crate::{
+    context::RenderContext,
+    error::Error as CanvasError,
+    input::{InputCaptureId, InputEvent},
+    profiling::FrameTiming,
+};
 
 /// The trait for a handling events during rendering.
 pub trait EventHandler {
-    /// Callback for initializing the OpenGL setup. This is called once before the first frame.
-    /// Returns an error message if the setup failed.
+    /// Callback for initializing the GPU resources of the handler, e.g., buffers, textures and
+    /// render pipelines. This is called once before the first frame. The device, the queue and
+    /// the surface format needed for the color targets of the pipelines are available through
+    /// `ctx`. Returns an error message if the setup failed.
     ///
     /// # Arguments
-    ///* `w` - The width of the rendering buffer
-    ///* `h` - The height of the rendering buffer
-    fn setup(&mut self, width: u32, height: u32) -> Result<(), Box<dyn Error>>;
+    ///* `ctx` - The context for accessing the GPU and the canvas. The properties of the frame
+    ///  the pipelines must match are available through `RenderContext::surface_info`, the
+    ///  initial scale factor of the window through `RenderContext::scale_factor` and the GPU
+    ///  through `RenderContext::adapter_details`.
+    ///* `width` - The width of the rendering buffer
+    ///* `height` - The height of the rendering buffer
+    fn setup(&mut self, ctx: &RenderContext, width: u32, height: u32)
+        -> Result<(), Box<dyn Error>>;
 
-    /// Callback if the event loop quit
+    /// Callback if the event loop quit. Is called on every exit path, i.e., when the window
+    /// has been closed, on `CanvasControl::request_exit`, if an error made the canvas quit and
+    /// if creating the canvas or the setup failed.
     fn stop(&mut self);
 
-    /// Render the next frame
-    fn next_frame(&mut self);
+    /// Is called when the user requests to close the window, e.g., by clicking the close
+    /// button or by pressing a quit shortcut. Returns true if the canvas should close, which is
+    /// followed by `stop`, and false for vetoing the request, e.g., for asking the user to save
+    /// unsaved changes first.
+    fn close_requested(&mut self) -> bool {
+        true
+    }
+
+    /// Callback for advancing the state of the handler, e.g., the simulation or animations,
+    /// before the next frame is rendered. Is called once per frame before `on_frame` and
+    /// `next_frame`, which then only have to record the draw commands. With
+    /// `CanvasOptions::fixed_timestep` it is called zero or more times per frame instead.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context of the canvas, e.g., for uploading the new state to the GPU.
+    ///* `dt` - The time since the previous frame in seconds, see `RenderContext::delta_time`,
+    ///  or the fixed time step.
+    fn update(&mut self, _ctx: &RenderContext, _dt: f32) {}
+
+    /// Render the next frame by recording draw commands into the render pass of the frame.
+    /// The pass has already been cleared, or loads the previous frame if the contents are
+    /// preserved, and is submitted by the canvas. Not called if `on_frame` or `encode_frame`
+    /// renders the frame.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU and the canvas.
+    ///* `pass` - The render pass targeting the texture of the frame.
+    fn next_frame<'a>(&'a mut self, ctx: &RenderContext, pass: &mut wgpu::RenderPass<'a>);
+
+    /// Low-level frame callback, which replaces the default render pass of the frame entirely.
+    /// Handlers implementing this own all encoding and have to submit their commands to the
+    /// queue themselves, the canvas only acquires and presents the surface texture.
+    /// Returns `None` if not implemented, in which case the default frame is rendered.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU.
+    ///* `view` - The view of the texture to render the frame into.
+    fn on_frame(
+        &mut self,
+        _ctx: &RenderContext,
+        _view: &wgpu::TextureView,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        None
+    }
+
+    /// Frame callback for rendering with multiple passes, e.g., a shadow pass followed by the
+    /// main pass. The handler begins its own passes on the encoder, e.g., with
+    /// `begin_default_pass` for the pass into the frame, and the canvas submits the commands
+    /// and presents the frame. The multisampled target, the depth buffer and the GPU timing
+    /// only apply to the default render pass of `next_frame`. Returns `None` if not
+    /// implemented, in which case the default frame is rendered. Not called if `on_frame`
+    /// renders the frame.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU and the canvas.
+    ///* `encoder` - The encoder to record the passes into.
+    ///* `view` - The view of the texture to render the frame into.
+    fn encode_frame(
+        &mut self,
+        _ctx: &RenderContext,
+        _encoder: &mut wgpu::CommandEncoder,
+        _view: &wgpu::TextureView,
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        None
+    }
+
+    /// Renders the overlay, e.g., the UI, at the native resolution on top of the scene. Is only
+    /// called if a scene resolution is set, see `CanvasControl::set_scene_resolution`. In this
+    /// case, `on_frame` and the default frame render into the scene texture, which is then
+    /// stretched over the view passed to this callback. The commands have to be submitted to
+    /// the queue by the handler.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU.
+    ///* `view` - The view of the texture to render the overlay into.
+    fn on_overlay(&mut self, _ctx: &RenderContext, _view: &wgpu::TextureView) {}
+
+    /// Is called after each attempt to render a frame.
+    ///
+    /// # Arguments
+    ///
+    ///* `presented` - True if the frame made it to the screen and false if it has been dropped.
+    fn frame_presented(&mut self, _presented: bool) {}
+
+    /// Is called with the timing of each frame if `CanvasOptions::frame_profiling` is set to
+    /// `FrameProfiling::Callback`, e.g., for streaming the timings into a profiler.
+    ///
+    /// # Arguments
+    ///
+    ///* `timing` - The timing of the frame.
+    fn frame_timing(&mut self, _timing: &FrameTiming) {}
+
+    /// Is called with the time the GPU spent on the render pass of a frame if
+    /// `CanvasOptions::gpu_timing` is enabled and supported by the adapter. The result arrives
+    /// a few frames later, and frames rendered meanwhile or by `on_frame` aren't measured.
+    ///
+    /// # Arguments
+    ///
+    ///* `micros` - The GPU time of the render pass in microseconds.
+    fn gpu_frame_time(&mut self, _micros: f64) {}
+
+    /// Is called with the screenshot requested by `CanvasControl::request_screenshot`.
+    ///
+    /// # Arguments
+    ///
+    ///* `width` - The width of the screenshot in pixels.
+    ///* `height` - The height of the screenshot in pixels.
+    ///* `rgba` - The tightly packed RGBA pixels, row by row from the top.
+    fn screenshot_ready(&mut self, _width: u32, _height: u32, _rgba: Vec<u8>) {}
+
+    /// Is called if rendering a frame failed, e.g., because the surface texture couldn't be
+    /// acquired repeatedly, or if a hot reloaded shader failed to compile.
+    ///
+    /// # Arguments
+    ///
+    ///* `error` - The error that occurred.
+    fn render_error(&mut self, _error: &CanvasError) {}
+
+    /// Is called when the window gained or lost the keyboard focus, e.g., for pausing a game or
+    /// releasing the cursor grab when the user switches to another window.
+    ///
+    /// # Arguments
+    ///
+    ///* `focused` - True if the window gained the focus and false if it lost it.
+    fn focus_changed(&mut self, _focused: bool) {}
+
+    /// Is called when the scale factor of the window changed, e.g., because it has been moved
+    /// to a monitor with a different pixel density, for rescaling fonts and layouts. The
+    /// resulting change of the surface size is reported by `resize` afterwards.
+    ///
+    /// # Arguments
+    ///
+    ///* `scale_factor` - The new number of physical pixels per logical pixel.
+    fn scale_factor_changed(&mut self, _scale_factor: f64) {}
+
+    /// Is called when rendering is suspended because the window has been minimized, is fully
+    /// hidden by other windows or has lost the focus if `CanvasOptions::suspend_when_unfocused`
+    /// is set. No frames are rendered until `on_resume` is called.
+    fn on_suspend(&mut self) {}
+
+    /// Is called when the window is visible again after `on_suspend` and rendering resumes.
+    fn on_resume(&mut self) {}
+
+    /// Is called whenever the event loop has processed all pending events and is about to wait,
+    /// right before the canvas decides whether to redraw. Can be used for lightweight
+    /// background work which is not tied to rendering, e.g., polling loaders.
+    fn idle(&mut self) {}
 
     /// Resizing the rendering buffer
     ///
@@ -27,13 +196,29 @@ pub trait EventHandler {
     ///* `h` - The height of the rendering buffer
     fn resize(&mut self, w: u32, h: u32);
 
-    /// Callback for logical cursor position
+    /// Callback for logical cursor position. The origin is the top-left corner of the window
+    /// unless configured otherwise by `CanvasOptions::cursor_origin`. Is called while the
+    /// cursor moves over the window, or while dragging outside of it, but not while the cursor
+    /// is locked, see `raw_mouse_motion`.
     ///
     ///* `x` - The x coordinate of the cursor in logical coordinates
     ///* `y` - The y coordinate of the cursor in logical coordinates
     fn cursor_move(&mut self, x: f64, y: f64);
 
-    /// Callback for mouse button event.
+    /// Is called with the relative motion of the mouse, independent of the cursor position,
+    /// e.g., for mouselook while the cursor is grabbed with `CanvasControl::set_cursor_grab`.
+    /// Unlike `cursor_move`, this keeps firing at the edges of the screen and while the cursor
+    /// is locked, but the deltas are unaccelerated and roughly in physical pixels. Both
+    /// callbacks are called for the same movement while the cursor is over the window.
+    ///
+    /// # Arguments
+    ///
+    ///* `dx` - The horizontal motion, positive to the right.
+    ///* `dy` - The vertical motion, positive downwards.
+    fn raw_mouse_motion(&mut self, _dx: f64, _dy: f64) {}
+
+    /// Callback for mouse button event. The coordinates have the same origin as in
+    /// `cursor_move`.
     ///
     /// # Arguments
     ///
@@ -41,13 +226,185 @@ pub trait EventHandler {
     ///* `y` - The y coordinate of the cursor in logical coordinates
     ///* `button` - The pressed/released mouse button
     ///* `pressed` - If true the mouse button was pressed and released otherwise.
-    fn mouse_button(&mut self, x: f64, y: f64, button: MouseButton, pressed: bool);
+    ///* `click_count` - The number of presses of the current click sequence, i.e., 1 for a
+    ///  single, 2 for a double and 3 for a triple click. Releases report the count of the
+    ///  preceding press. See `CanvasOptions::double_click_interval`.
+    ///* `modifiers` - The modifier keys held down, e.g., for Ctrl+Click.
+    fn mouse_button(
+        &mut self,
+        x: f64,
+        y: f64,
+        button: MouseButton,
+        pressed: bool,
+        click_count: u32,
+        modifiers: ModifiersState,
+    );
+
+    /// Is called when the mouse wheel or the touchpad is scrolled. The deltas are always given
+    /// in lines: positive values scroll up and to the left, i.e., away from the user. Pixel
+    /// deltas reported by touchpads are normalized to lines assuming a line height of
+    /// `input::SCROLL_LINE_HEIGHT` logical pixels, so both devices scroll at the same speed.
+    ///
+    /// # Arguments
+    ///
+    ///* `delta_x` - The horizontal scroll distance in lines.
+    ///* `delta_y` - The vertical scroll distance in lines.
+    fn mouse_wheel(&mut self, _delta_x: f32, _delta_y: f32) {}
+
+    /// Is called for every touch of a touchscreen. The coordinates have the same origin as in
+    /// `cursor_move`. Each finger has its own id, which stays the same from `Started` until
+    /// `Ended` or `Cancelled`.
+    ///
+    /// # Arguments
+    ///
+    ///* `phase` - The phase of the touch.
+    ///* `id` - The id of the finger, which is unique among the active touches.
+    ///* `x` - The x coordinate of the touch in logical coordinates
+    ///* `y` - The y coordinate of the touch in logical coordinates
+    fn touch(&mut self, _phase: TouchPhase, _id: u64, _x: f64, _y: f64) {}
+
+    /// Is called for pinch gestures on the touchpad, e.g., for zooming. Only reported on
+    /// macOS.
+    ///
+    /// # Arguments
+    ///
+    ///* `delta` - The change of the zoom factor, positive for zooming in.
+    ///* `phase` - The phase of the gesture.
+    fn pinch_gesture(&mut self, _delta: f64, _phase: TouchPhase) {}
+
+    /// Is called for rotation gestures on the touchpad. Only reported on macOS.
+    ///
+    /// # Arguments
+    ///
+    ///* `delta` - The rotation in degrees, counterclockwise for positive values.
+    ///* `phase` - The phase of the gesture.
+    fn rotation_gesture(&mut self, _delta: f32, _phase: TouchPhase) {}
 
     /// Is called when a key is either pressed or released.
     ///
     /// # Arguments
     ///
-    /// * `key` - The key pressed or released.
+    /// * `key` - The logical key pressed or released, which depends on the keyboard layout.
+    /// * `physical` - The physical key, i.e., the position of the key on the keyboard, e.g.,
+    ///   for binding WASD controls independent of the layout.
     /// * `pressed` - Determines if the key was pressed or released.
-    fn keyboard_event(&mut self, key: Key, pressed: bool);
+    /// * `repeat` - True if the event is an automatic repeat of a held down key, which text
+    ///   input usually wants and game controls usually ignore.
+    /// * `modifiers` - The modifier keys held down, e.g., for Ctrl+S.
+    fn keyboard_event(
+        &mut self,
+        key: Key,
+        physical: PhysicalKey,
+        pressed: bool,
+        repeat: bool,
+        modifiers: ModifiersState,
+    );
+
+    /// Is called for every key event with the physical key, i.e., the position of the key on
+    /// the keyboard, independent of the keyboard layout and IME composition. This is called
+    /// before `keyboard_event` and not at all for keys the platform can't identify.
+    ///
+    /// # Arguments
+    ///
+    /// * `physical_key` - The physical key pressed or released.
+    /// * `state` - Determines if the key was pressed or released.
+    /// * `repeat` - True if the event is an automatic repeat of a held down key.
+    fn raw_key(&mut self, _physical_key: KeyCode, _state: ElementState, _repeat: bool) {}
+
+    /// Receives all input events while an exclusive input capture is active, bypassing the
+    /// regular input callbacks. See `CanvasControl::push_input_capture`.
+    ///
+    /// # Arguments
+    ///
+    /// * `capture` - The id of the active input capture.
+    /// * `event` - The captured input event.
+    fn captured_input(&mut self, _capture: InputCaptureId, _event: &InputEvent) {}
+
+    /// Receives the input events buffered while the canvas was paused, in the order they
+    /// occurred. Is called on resume if `CanvasOptions::paused_input` is `PausedInput::Defer`
+    /// and any events have been received.
+    ///
+    /// # Arguments
+    ///
+    ///* `events` - The input events received while the canvas was paused.
+    fn deferred_input(&mut self, _events: Vec<InputEvent>) {}
+
+    /// Is called after shaders loaded with `RenderContext::load_shader` have been modified on
+    /// disk and recompiled successfully, so the handler can rebuild its pipelines by loading
+    /// the shaders again. Shaders which fail to compile are reported to `render_error` instead.
+    /// Only called if the `hot-reload` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU.
+    fn shaders_reloaded(&mut self, _ctx: &RenderContext) {}
+
+    /// Is called on the event loop thread once an asset requested by
+    /// `CanvasControl::load_asset` has been decoded, e.g., for uploading it to the GPU. The
+    /// asset has the type returned by the decoder and can be downcast accordingly.
+    /// Only called if the `assets` feature is enabled.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU.
+    ///* `id` - The id returned by `CanvasControl::load_asset`.
+    ///* `asset` - The decoded asset or the error returned by the decoder.
+    #[cfg(feature = "assets")]
+    fn asset_loaded(
+        &mut self,
+        _ctx: &RenderContext,
+        _id: AssetId,
+        _asset: std::result::Result<Asset, CanvasError>,
+    ) {
+    }
+
+    /// Is called after the format of the surface has changed, e.g., because HDR has been
+    /// toggled by the OS and the preferred format of the surface changed, or because the
+    /// handler applied a configuration with a different format. The handler has to rebuild
+    /// its pipelines with the new color target format. The offscreen targets of the canvas
+    /// have already been recreated.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the GPU.
+    ///* `format` - The new format of the surface.
+    fn surface_format_changed(&mut self, _ctx: &RenderContext, _format: wgpu::TextureFormat) {}
+
+    /// Is called after the error policy recovered the canvas with `RecoveryAction::Recover`,
+    /// i.e., recreated the device and the surface. All resources created with the previous
+    /// device are invalid and have to be recreated. Calls `setup` again by default. Returns an
+    /// error message if the resources couldn't be recreated, which makes the canvas exit.
+    ///
+    /// # Arguments
+    ///
+    ///* `ctx` - The context for accessing the new device.
+    ///* `width` - The width of the rendering buffer
+    ///* `height` - The height of the rendering buffer
+    fn device_recovered(
+        &mut self,
+        ctx: &RenderContext,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Box<dyn Error>> {
+        self.setup(ctx, width, height)
+    }
+
+    /// Is called when a file has been dropped onto the window, e.g., for loading a model or an
+    /// image. Dropping multiple files calls this once per file.
+    ///
+    /// # Arguments
+    ///
+    ///* `path` - The path of the dropped file.
+    fn file_dropped(&mut self, _path: PathBuf) {}
+
+    /// Is called when a file is dragged over the window, e.g., for highlighting the drop area.
+    /// Dragging multiple files calls this once per file.
+    ///
+    /// # Arguments
+    ///
+    ///* `path` - The path of the hovered file.
+    fn file_hovered(&mut self, _path: PathBuf) {}
+
+    /// Is called when the hovered files have left the window without being dropped.
+    fn file_hover_cancelled(&mut self) {}
 }
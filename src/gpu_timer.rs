@@ -0,0 +1,144 @@
+use std::{sync::mpsc, time::Duration};
+
+use crate::error::{Error, Result};
+
+/// The number of timestamps written per frame, i.e., at the beginning and the end of the pass.
+const TIMESTAMP_COUNT: u32 = 2;
+
+/// The size of the buffers the timestamps are resolved into.
+const TIMESTAMP_BUFFER_SIZE: u64 = TIMESTAMP_COUNT as u64 * std::mem::size_of::<u64>() as u64;
+
+/// Measures the time the GPU spends on the render pass of a frame with timestamp queries.
+/// Only one measurement is in flight at a time, frames rendered while the result of the
+/// previous one is being read back aren't measured.
+pub(crate) struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    /// The number of nanoseconds per timestamp tick.
+    period: f32,
+    /// True if timestamps have been written and their result hasn't been read yet.
+    busy: bool,
+    mapped: Option<mpsc::Receiver<std::result::Result<(), wgpu::BufferAsyncError>>>,
+}
+
+impl GpuTimer {
+    /// Creates the query set and the buffers for reading back the timestamps.
+    ///
+    /// # Arguments
+    /// * `device` - The device created with `wgpu::Features::TIMESTAMP_QUERY`.
+    /// * `queue` - The command queue of the device.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Timer Queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: TIMESTAMP_COUNT,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Resolve Buffer"),
+            size: TIMESTAMP_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Timer Readback Buffer"),
+            size: TIMESTAMP_BUFFER_SIZE,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            period: queue.get_timestamp_period(),
+            busy: false,
+            mapped: None,
+        }
+    }
+
+    /// Returns the timestamp writes for the render pass of the frame, or `None` if the
+    /// previous measurement hasn't been read back yet.
+    pub fn timestamp_writes(&self) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        if self.busy {
+            return None;
+        }
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        })
+    }
+
+    /// Records resolving the timestamps written by the render pass and copying them into the
+    /// readback buffer. The commands have to be submitted before calling `map`.
+    ///
+    /// # Arguments
+    /// * `encoder` - The encoder the render pass has been recorded into.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..TIMESTAMP_COUNT, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.readback_buffer,
+            0,
+            TIMESTAMP_BUFFER_SIZE,
+        );
+
+        self.busy = true;
+    }
+
+    /// Starts mapping the readback buffer if timestamps have been resolved, which completes
+    /// once the GPU is done with the frame and the device has been polled.
+    pub fn map(&mut self) {
+        if !self.busy || self.mapped.is_some() {
+            return;
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+
+        self.mapped = Some(receiver);
+    }
+
+    /// Returns the time the GPU spent on the measured render pass, or `None` if no result is
+    /// available yet.
+    pub fn try_read(&mut self) -> Option<Result<Duration>> {
+        let result = match self.mapped.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => Err(wgpu::BufferAsyncError),
+        };
+
+        self.mapped = None;
+        self.busy = false;
+
+        if let Err(e) = result {
+            return Some(Err(Error::GraphicsAPI(format!(
+                "Failed to map the GPU timer buffer: {}",
+                e
+            ))));
+        }
+
+        let timestamps: Vec<u64> = self
+            .readback_buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks_exact(std::mem::size_of::<u64>())
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap_or_default()))
+            .collect();
+        self.readback_buffer.unmap();
+
+        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+        Some(Ok(Duration::from_nanos(
+            (ticks as f64 * self.period as f64) as u64,
+        )))
+    }
+}
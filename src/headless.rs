@@ -0,0 +1,452 @@
+use log::{error, info};
+
+use crate::{
+    canvas::{
+        check_clear_values, check_downlevel_capabilities, choose_adapter, color_attachment,
+        depth_stencil_attachment, request_device, select_sample_count, update_attachment,
+        CanvasOptions, CLEAR_COLOR,
+    },
+    clock::MonotonicClock,
+    context::RenderContext,
+    control::{CanvasControl, CanvasController},
+    depth::DepthConfig,
+    error::{Error, Result},
+    event_handler::EventHandler,
+    readback::read_texture,
+    texture::{Texture, TextureOptions},
+};
+
+/// The format of the texture a headless canvas renders into. The pixels of the final frame
+/// are returned as tightly packed RGBA bytes in this format.
+pub const HEADLESS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+/// Renders the given number of frames of the handler into an offscreen texture without any
+/// window or surface, e.g., for visual regression tests in CI or for generating thumbnails on a
+/// server. Returns the final frame as tightly packed RGBA bytes in `HEADLESS_FORMAT`, row by
+/// row from the top.
+///
+/// `setup` is called before the first frame and `stop` after the last one, the frames are
+/// rendered like the ones of a windowed canvas with the default options. As there is no window,
+/// none of the input callbacks are called, and the methods of `CanvasControl` that need the
+/// window or the event loop do nothing or return an error. See
+/// `create_and_run_headless_with_options` for rendering with other options.
+///
+/// # Arguments
+/// * `width` - The width of the frames in pixels.
+/// * `height` - The height of the frames in pixels.
+/// * `frames` - The number of frames to render, which must be at least one.
+/// * `handler` - The handler rendering the frames.
+pub async fn create_and_run_headless<H>(
+    width: u32,
+    height: u32,
+    frames: u64,
+    handler: H,
+) -> Result<Vec<u8>>
+where
+    H: EventHandler,
+{
+    create_and_run_headless_with_options(CanvasOptions::default(), width, height, frames, handler)
+        .await
+}
+
+/// Renders the given number of frames of the handler into an offscreen texture like
+/// `create_and_run_headless`, but with the given options. The options selecting the adapter
+/// and creating the device, i.e., the backends, the features, the limits and
+/// `CanvasOptions::choose_adapter`, are applied as well as the depth buffer, the sample count
+/// and the time source. The options of the window and the surface are ignored, e.g., the size,
+/// the format and the scaling mode.
+///
+/// # Arguments
+/// * `options` - The options for creating the canvas.
+/// * `width` - The width of the frames in pixels.
+/// * `height` - The height of the frames in pixels.
+/// * `frames` - The number of frames to render, which must be at least one.
+/// * `handler` - The handler rendering the frames.
+pub async fn create_and_run_headless_with_options<H>(
+    options: CanvasOptions,
+    width: u32,
+    height: u32,
+    frames: u64,
+    mut handler: H,
+) -> Result<Vec<u8>>
+where
+    H: EventHandler,
+{
+    info!("Creating headless canvas...");
+
+    let canvas = match HeadlessCanvas::new(options, width, height, frames).await {
+        Ok(canvas) => canvas,
+        Err(err) => {
+            // the handler is stopped on every exit path, even if it has never been set up
+            handler.stop();
+            return Err(err);
+        }
+    };
+    let HeadlessCanvas {
+        device,
+        queue,
+        control,
+        config,
+        targets,
+    } = canvas;
+
+    let ctx = RenderContext::new(&device, &queue, &control, &config, (width, height), 0)
+        .with_depth_view(targets.depth.as_ref().map(|depth| &depth.view));
+    if let Err(err) = handler.setup(&ctx, width, height) {
+        error!("Error during setup: {}", err);
+        handler.stop();
+        return Err(Error::Internal(format!("Error during setup: {}", err)));
+    }
+
+    let result = render_frames(
+        &device,
+        &queue,
+        &control,
+        &config,
+        &targets,
+        frames,
+        &mut handler,
+    )
+    .and_then(|_| read_texture(&device, &queue, &targets.color.texture));
+    handler.stop();
+
+    result
+}
+
+/// The device and the targets of a headless canvas, which are created before the setup of the
+/// handler.
+struct HeadlessCanvas {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    control: CanvasControl,
+    /// The configuration standing in for the surface.
+    config: wgpu::SurfaceConfiguration,
+    targets: HeadlessTargets,
+}
+
+impl HeadlessCanvas {
+    /// Validates the options and creates the device and the targets.
+    ///
+    /// # Arguments
+    /// * `options` - The options for creating the canvas.
+    /// * `width` - The width of the frames in pixels.
+    /// * `height` - The height of the frames in pixels.
+    /// * `frames` - The number of frames to render, which must be at least one.
+    async fn new(mut options: CanvasOptions, width: u32, height: u32, frames: u64) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(Error::Internal(format!(
+                "Invalid headless canvas size {}x{}",
+                width, height
+            )));
+        }
+
+        if frames == 0 {
+            return Err(Error::Internal(
+                "A headless canvas must render at least one frame".to_string(),
+            ));
+        }
+
+        check_clear_values(&options)?;
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: options.backends,
+            flags: options.instance_flags,
+            dx12_shader_compiler: options.dx12_shader_compiler.clone(),
+            gles_minor_version: options.backend_options.gles_minor_version,
+        });
+
+        let chosen_adapter = match options.choose_adapter {
+            Some(choose) => choose_adapter(choose, &instance, None)?,
+            None => None,
+        };
+        let adapter = match chosen_adapter {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: options.power_preference,
+                    compatible_surface: None,
+                    force_fallback_adapter: false,
+                })
+                .await
+                .ok_or_else(|| Error::GraphicsAPI("No suitable adapter found".to_string()))?,
+        };
+
+        let adapter_info = adapter.get_info();
+        info!("Adapter name: {}", adapter_info.name);
+        info!("Adapter backend API: {}", adapter_info.backend.to_str());
+
+        check_downlevel_capabilities(&options, &adapter)?;
+        let (device, queue) = request_device(&options, &adapter).await?;
+
+        // there is no surface, but the handler may still query the format and the size
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: HEADLESS_FORMAT,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        };
+
+        let sample_count = select_sample_count(
+            options.sample_count,
+            &adapter,
+            &device,
+            HEADLESS_FORMAT,
+            options.depth.map(|depth| depth.format),
+        )?;
+
+        let time_source =
+            std::mem::replace(&mut options.time_source, Box::new(MonotonicClock::new()));
+        let control = CanvasControl::new(
+            None,
+            instance,
+            adapter_info,
+            config.clone(),
+            &options,
+            time_source,
+            CanvasController::headless(),
+        )?;
+        control.set_sample_count(sample_count);
+
+        let targets = HeadlessTargets::new(&device, &options, &config, sample_count)?;
+
+        Ok(Self {
+            device,
+            queue,
+            control,
+            config,
+            targets,
+        })
+    }
+}
+
+/// The textures a headless canvas renders into.
+struct HeadlessTargets {
+    /// The texture holding the final frame.
+    color: Texture,
+    /// The multisampled color texture, which is resolved into the frame, if multisampling is
+    /// enabled.
+    msaa: Option<Texture>,
+    /// The depth buffer, if any.
+    depth: Option<Texture>,
+    /// The configuration of the depth buffer, if any.
+    depth_config: Option<DepthConfig>,
+    /// The value the stencil buffer is cleared to.
+    clear_stencil: u32,
+}
+
+impl HeadlessTargets {
+    /// Creates the textures for frames of the size of the configuration.
+    ///
+    /// # Arguments
+    /// * `device` - The device used for rendering.
+    /// * `options` - The options with the depth buffer and the clear values.
+    /// * `config` - The configuration standing in for the surface.
+    /// * `sample_count` - The number of samples per pixel of the default render pass.
+    fn new(
+        device: &wgpu::Device,
+        options: &CanvasOptions,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Result<Self> {
+        let size = (config.width, config.height);
+        let color = Texture::new_2d(
+            device,
+            config.width,
+            config.height,
+            &TextureOptions {
+                label: Some("Headless Target"),
+                format: config.format,
+                usage: config.usage,
+                mipmapped: false,
+            },
+        )?;
+
+        let msaa = (sample_count > 1).then(|| {
+            update_attachment(
+                device,
+                None,
+                "Multisampled Frame",
+                size,
+                config.format,
+                sample_count,
+            )
+        });
+
+        let depth = options.depth.map(|depth| {
+            update_attachment(
+                device,
+                None,
+                "Depth Buffer",
+                size,
+                depth.format,
+                sample_count,
+            )
+        });
+
+        Ok(Self {
+            color,
+            msaa,
+            depth,
+            depth_config: options.depth,
+            clear_stencil: options.clear_stencil,
+        })
+    }
+
+    /// Begins the default render pass of a frame, which clears the frame and the depth buffer.
+    ///
+    /// # Arguments
+    /// * `encoder` - The encoder of the frame.
+    fn begin_pass<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &[Some(color_attachment(
+                &self.color.view,
+                self.msaa.as_ref(),
+                wgpu::LoadOp::Clear(CLEAR_COLOR),
+            ))],
+            depth_stencil_attachment: self
+                .depth
+                .as_ref()
+                .zip(self.depth_config)
+                .map(|(target, depth)| depth_stencil_attachment(target, depth, self.clear_stencil)),
+            occlusion_query_set: None,
+            timestamp_writes: None,
+        })
+    }
+}
+
+/// Renders the given number of frames of the handler into the target.
+///
+/// # Arguments
+/// * `device` - The device used for rendering.
+/// * `queue` - The command queue of the device.
+/// * `control` - The control handle of the canvas.
+/// * `config` - The configuration standing in for the surface.
+/// * `targets` - The textures the frames are rendered into.
+/// * `frames` - The number of frames to render.
+/// * `handler` - The handler rendering the frames.
+fn render_frames<H: EventHandler>(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    control: &CanvasControl,
+    config: &wgpu::SurfaceConfiguration,
+    targets: &HeadlessTargets,
+    frames: u64,
+    handler: &mut H,
+) -> Result<()> {
+    let size = (config.width, config.height);
+    let mut last_frame_time = None;
+
+    for frame_index in 0..frames {
+        let now = control.now();
+        let delta_time = last_frame_time
+            .map(|last| now.saturating_sub(last).as_secs_f32())
+            .unwrap_or(0.0);
+        last_frame_time = Some(now);
+
+        let ctx = RenderContext::new(device, queue, control, config, size, frame_index)
+            .with_delta_time(delta_time)
+            .with_depth_view(targets.depth.as_ref().map(|depth| &depth.view));
+        handler.update(&ctx, delta_time);
+
+        // give the handler the chance to take over the whole frame
+        if let Some(result) = handler.on_frame(&ctx, &targets.color.view) {
+            result.map_err(|e| Error::Internal(format!("Error during frame: {}", e)))?;
+            continue;
+        }
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Render Encoder"),
+        });
+
+        if let Some(result) = handler.encode_frame(&ctx, &mut encoder, &targets.color.view) {
+            result.map_err(|e| Error::Internal(format!("Error during frame: {}", e)))?;
+        } else {
+            let mut render_pass = targets.begin_pass(&mut encoder);
+            handler.next_frame(&ctx, &mut render_pass);
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, error::Error as StdError, rc::Rc};
+
+    use winit::{
+        event::MouseButton,
+        keyboard::{Key, ModifiersState, PhysicalKey},
+    };
+
+    use super::*;
+
+    /// A handler ignoring all callbacks except for recording that it has been stopped.
+    #[derive(Default)]
+    struct StopHandler {
+        stopped: Rc<Cell<bool>>,
+    }
+
+    impl EventHandler for StopHandler {
+        fn setup(
+            &mut self,
+            _ctx: &RenderContext,
+            _width: u32,
+            _height: u32,
+        ) -> std::result::Result<(), Box<dyn StdError>> {
+            Ok(())
+        }
+
+        fn stop(&mut self) {
+            self.stopped.set(true);
+        }
+
+        fn next_frame<'a>(&'a mut self, _ctx: &RenderContext, _pass: &mut wgpu::RenderPass<'a>) {}
+
+        fn resize(&mut self, _w: u32, _h: u32) {}
+
+        fn cursor_move(&mut self, _x: f64, _y: f64) {}
+
+        fn mouse_button(
+            &mut self,
+            _x: f64,
+            _y: f64,
+            _button: MouseButton,
+            _pressed: bool,
+            _click_count: u32,
+            _modifiers: ModifiersState,
+        ) {
+        }
+
+        fn keyboard_event(
+            &mut self,
+            _key: Key,
+            _physical: PhysicalKey,
+            _pressed: bool,
+            _repeat: bool,
+            _modifiers: ModifiersState,
+        ) {
+        }
+    }
+
+    #[test]
+    fn invalid_size_stops_handler() {
+        let handler = StopHandler::default();
+        let stopped = handler.stopped.clone();
+        let result = pollster::block_on(create_and_run_headless_with_options(
+            CanvasOptions::default(),
+            0,
+            16,
+            1,
+            handler,
+        ));
+
+        assert!(result.is_err());
+        assert!(stopped.get());
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::{error::Result, shader::load_shader};
+
+/// The interval in which the watched files are checked for modifications.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The state of a watched shader file.
+struct WatchedShader {
+    /// The last known modification time of the file.
+    modified: Option<SystemTime>,
+    /// The module compiled after the last modification, which hasn't been picked up by the
+    /// handler yet.
+    reloaded: Option<wgpu::ShaderModule>,
+}
+
+/// Watches the shaders loaded by the handler and recompiles them when they are modified
+/// on disk.
+pub(crate) struct ShaderWatcher {
+    shaders: RefCell<HashMap<PathBuf, WatchedShader>>,
+    last_poll: Cell<Instant>,
+}
+
+impl ShaderWatcher {
+    /// Creates a new watcher without any watched shaders.
+    pub fn new() -> Self {
+        Self {
+            shaders: RefCell::new(HashMap::new()),
+            last_poll: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Loads the shader at the given path and starts watching it. Returns the module which
+    /// has been compiled by the last reload, if the handler hasn't picked it up yet.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the shader module on.
+    /// * `path` - The path of the WGSL file.
+    pub fn load(&self, device: &wgpu::Device, path: &Path) -> Result<wgpu::ShaderModule> {
+        let mut shaders = self.shaders.borrow_mut();
+
+        if let Some(module) = shaders
+            .get_mut(path)
+            .and_then(|shader| shader.reloaded.take())
+        {
+            return Ok(module);
+        }
+
+        // query the time before reading, so modifications while reading aren't missed
+        let modified = modification_time(path);
+        let result = load_shader(device, path);
+
+        // watch the file even if it failed to compile, so fixing the error triggers a reload
+        shaders.insert(
+            path.to_path_buf(),
+            WatchedShader {
+                modified,
+                reloaded: None,
+            },
+        );
+
+        result
+    }
+
+    /// Recompiles all watched shaders which have been modified since they were loaded.
+    /// Returns the result of compiling each modified shader, i.e., an empty list if no
+    /// shader has been modified.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the shader modules on.
+    pub fn poll(&self, device: &wgpu::Device) -> Vec<Result<()>> {
+        if self.last_poll.get().elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll.set(Instant::now());
+
+        let mut results = Vec::new();
+        for (path, shader) in self.shaders.borrow_mut().iter_mut() {
+            let modified = modification_time(path);
+            if modified == shader.modified {
+                continue;
+            }
+
+            shader.modified = modified;
+            results.push(load_shader(device, path).map(|module| {
+                shader.reloaded = Some(module);
+            }));
+        }
+
+        results
+    }
+}
+
+/// Returns the time the given file has been modified the last time, if available.
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,106 @@
+use crate::event_handler::{
+    ElementState, Key, KeyCode, ModifiersState, MouseButton, PhysicalKey, TouchPhase,
+};
+
+/// An input event as it is dispatched to the event handler.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    /// The cursor moved to the given logical coordinates.
+    CursorMoved { x: f64, y: f64 },
+
+    /// A mouse button was pressed or released at the given logical coordinates while the
+    /// given modifier keys were held down. `click_count` is the number of presses of the
+    /// current click sequence, e.g., 2 for a double click.
+    MouseButton {
+        x: f64,
+        y: f64,
+        button: MouseButton,
+        pressed: bool,
+        click_count: u32,
+        modifiers: ModifiersState,
+    },
+
+    /// The mouse moved by the given raw delta, independent of the cursor.
+    RawMouseMotion { delta_x: f64, delta_y: f64 },
+
+    /// The finger with the given id touched, moved on or left the touchscreen at the given
+    /// logical coordinates.
+    Touch {
+        phase: TouchPhase,
+        id: u64,
+        x: f64,
+        y: f64,
+    },
+
+    /// A pinch gesture on the touchpad changed the zoom by the given factor delta, positive
+    /// for zooming in.
+    PinchGesture { delta: f64, phase: TouchPhase },
+
+    /// A rotation gesture on the touchpad rotated by the given angle in degrees,
+    /// counterclockwise for positive values.
+    RotationGesture { delta: f32, phase: TouchPhase },
+
+    /// The mouse wheel or the touchpad was scrolled by the given number of lines.
+    MouseWheel { delta_x: f32, delta_y: f32 },
+
+    /// A key was pressed or released while the given modifier keys were held down. `repeat`
+    /// is true for the automatic repeats of a held down key.
+    Keyboard {
+        key: Key,
+        physical: PhysicalKey,
+        pressed: bool,
+        repeat: bool,
+        modifiers: ModifiersState,
+    },
+
+    /// A key at the given physical position was pressed or released.
+    RawKey {
+        physical_key: KeyCode,
+        state: ElementState,
+        repeat: bool,
+    },
+}
+
+/// The height of a line in logical pixels, which is used for normalizing the pixel deltas of
+/// touchpads to the line deltas of mouse wheels.
+pub const SCROLL_LINE_HEIGHT: f64 = 20.0;
+
+/// The input state tracked by the canvas, e.g., for recording it together with a sequence of
+/// input events and restoring it before replaying the events in tests and demos. All fields are
+/// plain data, so the state can be serialized in any format.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputState {
+    /// The cursor position in logical coordinates with the top-left origin.
+    pub cursor: (f64, f64),
+    /// The mouse buttons that are currently held down.
+    pub pressed_buttons: Vec<MouseButton>,
+    /// The physical keys that are currently held down.
+    pub pressed_keys: Vec<KeyCode>,
+    /// The current state of the modifier keys.
+    pub modifiers: ModifiersState,
+}
+
+/// The origin of the logical coordinates of the cursor reported to the event handler.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CursorOrigin {
+    /// The origin is the top-left corner of the window and y points down, as in winit and wgpu.
+    #[default]
+    TopLeft,
+    /// The origin is the bottom-left corner of the window and y points up, as in OpenGL. The y
+    /// coordinate is flipped using the current height of the surface.
+    BottomLeft,
+}
+
+/// Determines what happens with the input events received while the canvas is paused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PausedInput {
+    /// The events are discarded.
+    #[default]
+    Drop,
+    /// The events are buffered and delivered to `EventHandler::deferred_input` on resume.
+    Defer,
+}
+
+/// Identifies an exclusive input capture created by `CanvasControl::push_input_capture`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputCaptureId(pub(crate) u64);
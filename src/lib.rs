@@ -0,0 +1,31 @@
+pub mod adapter;
+#[cfg(feature = "assets")]
+pub mod assets;
+mod blit;
+#[cfg(feature = "camera")]
+pub mod camera;
+pub mod canvas;
+pub mod clock;
+pub mod context;
+pub mod control;
+pub mod depth;
+pub mod error;
+pub mod event_handler;
+mod gpu_timer;
+pub mod headless;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+pub mod input;
+pub mod memory;
+mod mirror;
+pub mod monitor;
+mod offscreen;
+pub mod profiling;
+mod readback;
+pub mod recovery;
+pub mod scaling;
+mod scene;
+pub mod shader;
+#[cfg(feature = "text")]
+mod text;
+pub mod texture;
@@ -1,16 +1,23 @@
-use canvas::create_and_run_canvas;
-use event_handler::EventHandler;
-use log::{debug, error, info, trace, LevelFilter};
+use std::process::ExitCode;
 
-mod canvas;
-mod error;
-mod event_handler;
+use log::{debug, info, trace, LevelFilter};
+use wgpu_prog::{
+    canvas::{self, run_main},
+    context::RenderContext,
+    event_handler::{EventHandler, Key, ModifiersState, MouseButton, PhysicalKey},
+};
 
 struct Handler {}
 
 impl EventHandler for Handler {
-    fn setup(&mut self, width: u32, height: u32) -> Result<(), Box<dyn std::error::Error>> {
+    fn setup(
+        &mut self,
+        ctx: &RenderContext,
+        width: u32,
+        height: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         info!("Setup canvas with width {} and height {}", width, height);
+        info!("Rendering with backend {}", ctx.control().backend_name());
 
         Ok(())
     }
@@ -19,8 +26,8 @@ impl EventHandler for Handler {
         info!("Stop canvas");
     }
 
-    fn next_frame(&mut self) {
-        trace!("Render Frame");
+    fn next_frame<'a>(&'a mut self, ctx: &RenderContext, _pass: &mut wgpu::RenderPass<'a>) {
+        trace!("Render Frame {}", ctx.frame_index());
     }
 
     fn resize(&mut self, w: u32, h: u32) {
@@ -31,21 +38,44 @@ impl EventHandler for Handler {
         trace!("Cursor moved to x {} and y {}", x, y);
     }
 
-    fn mouse_button(&mut self, x: f64, y: f64, button: winit::event::MouseButton, pressed: bool) {
+    fn mouse_button(
+        &mut self,
+        x: f64,
+        y: f64,
+        button: MouseButton,
+        pressed: bool,
+        click_count: u32,
+        modifiers: ModifiersState,
+    ) {
         debug!(
-            "Mouse button {:?} at x {} and y {} was {}",
+            "Mouse button {:?} at x {} and y {} was {} ({} clicks) with modifiers {:?}",
             button,
             x,
             y,
-            if pressed { "pressed" } else { "released" }
+            if pressed { "pressed" } else { "released" },
+            click_count,
+            modifiers
         );
     }
 
-    fn keyboard_event(&mut self, key: winit::keyboard::Key, pressed: bool) {
+    fn keyboard_event(
+        &mut self,
+        key: Key,
+        physical: PhysicalKey,
+        pressed: bool,
+        repeat: bool,
+        modifiers: ModifiersState,
+    ) {
+        if repeat {
+            return;
+        }
+
         debug!(
-            "Key {:?} was {}",
+            "Key {:?} ({:?}) was {} with modifiers {:?}",
             key,
-            if pressed { "pressed" } else { "released" }
+            physical,
+            if pressed { "pressed" } else { "released" },
+            modifiers
         );
     }
 }
@@ -54,20 +84,17 @@ fn initialize_logging(filter: LevelFilter) {
     env_logger::builder().filter_level(filter).init();
 }
 
-fn main() {
+fn main() -> ExitCode {
     initialize_logging(LevelFilter::Info);
 
     let options = canvas::CanvasOptions {
         width: 800,
         height: 600,
         title: "Hello World".to_string(),
+        ..Default::default()
     };
 
     let handler = Handler {};
 
-    pollster::block_on(async {
-        if let Err(err) = create_and_run_canvas(options, handler).await {
-            error!("Error: {}", err);
-        }
-    });
+    run_main(options, handler)
 }
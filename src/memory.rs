@@ -0,0 +1,62 @@
+/// An estimate of the GPU resources allocated by the device, e.g., for watching the growth of
+/// allocations in long-running applications to track down leaks.
+///
+/// wgpu doesn't expose the sizes of the underlying allocations, so the report counts the live
+/// resources per type instead of bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GpuMemoryReport {
+    pub buffers: usize,
+    pub textures: usize,
+    pub texture_views: usize,
+    pub samplers: usize,
+    pub bind_groups: usize,
+    pub shader_modules: usize,
+    pub render_pipelines: usize,
+    pub compute_pipelines: usize,
+    pub query_sets: usize,
+}
+
+impl GpuMemoryReport {
+    /// Generates the report for the given backend of the instance.
+    /// Returns `None` if the backend doesn't support reports.
+    ///
+    /// # Arguments
+    /// * `instance` - The instance the device has been created with.
+    /// * `backend` - The backend of the device.
+    #[cfg(all(feature = "memory-report", not(target_arch = "wasm32")))]
+    pub(crate) fn generate(instance: &wgpu::Instance, backend: wgpu::Backend) -> Option<Self> {
+        let report = instance.generate_report();
+
+        let hub = match backend {
+            #[cfg(any(windows, all(unix, not(target_os = "ios"), not(target_os = "macos"))))]
+            wgpu::Backend::Vulkan => report.vulkan,
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            wgpu::Backend::Metal => report.metal,
+            #[cfg(windows)]
+            wgpu::Backend::Dx12 => report.dx12,
+            #[cfg(windows)]
+            wgpu::Backend::Dx11 => report.dx11,
+            #[cfg(any(windows, all(unix, not(target_os = "ios"), not(target_os = "macos"))))]
+            wgpu::Backend::Gl => report.gl,
+            _ => None,
+        }?;
+
+        Some(Self {
+            buffers: hub.buffers.num_occupied,
+            textures: hub.textures.num_occupied,
+            texture_views: hub.texture_views.num_occupied,
+            samplers: hub.samplers.num_occupied,
+            bind_groups: hub.bind_groups.num_occupied,
+            shader_modules: hub.shader_modules.num_occupied,
+            render_pipelines: hub.render_pipelines.num_occupied,
+            compute_pipelines: hub.compute_pipelines.num_occupied,
+            query_sets: hub.query_sets.num_occupied,
+        })
+    }
+
+    /// Reports are only supported with the `memory-report` feature on native platforms.
+    #[cfg(not(all(feature = "memory-report", not(target_arch = "wasm32"))))]
+    pub(crate) fn generate(_instance: &wgpu::Instance, _backend: wgpu::Backend) -> Option<Self> {
+        None
+    }
+}
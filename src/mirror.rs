@@ -0,0 +1,143 @@
+use winit::window::{Window, WindowId};
+
+use crate::{
+    blit::Blitter,
+    error::{Error, Result},
+    offscreen::begin_surface_pass,
+    scaling::letterboxed_viewport,
+};
+
+/// A secondary window showing the same content as the canvas, e.g., on a projector. The
+/// content is rendered once into an offscreen target and drawn onto both surfaces.
+pub(crate) struct Mirror {
+    // the surface must be dropped before the window it has been created for
+    surface: wgpu::Surface,
+    window: Window,
+    config: wgpu::SurfaceConfiguration,
+    blitter: Blitter,
+}
+
+impl Mirror {
+    /// Creates the surface for the given window and the pipeline for drawing the content.
+    ///
+    /// # Arguments
+    /// * `instance` - The instance the device has been created with.
+    /// * `adapter` - The adapter of the device.
+    /// * `device` - The device used for rendering.
+    /// * `window` - The secondary window.
+    /// * `format` - The format of the content, which is preferred for the surface.
+    pub fn new(
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        window: Window,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self> {
+        // # Safety
+        //
+        // The surface is dropped before the window, see the order of the fields.
+        let surface = unsafe { instance.create_surface(&window) }
+            .map_err(|e| Error::GraphicsAPI(format!("{}", e)))?;
+
+        let caps = surface.get_capabilities(adapter);
+        if caps.formats.is_empty() {
+            return Err(Error::GraphicsAPI(
+                "Adapter can't present to the mirror window".to_string(),
+            ));
+        }
+
+        let surface_format = if caps.formats.contains(&format) {
+            format
+        } else {
+            caps.formats
+                .iter()
+                .copied()
+                .find(|f| f.is_srgb() == format.is_srgb())
+                .unwrap_or(caps.formats[0])
+        };
+
+        let size = window.inner_size();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: surface_format,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: caps.present_modes[0],
+            alpha_mode: caps.alpha_modes[0],
+            view_formats: vec![],
+        };
+        surface.configure(device, &config);
+
+        Ok(Self {
+            surface,
+            window,
+            config,
+            blitter: Blitter::new(device, surface_format, wgpu::FilterMode::Linear),
+        })
+    }
+
+    /// Returns the secondary window.
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    /// Returns the id of the secondary window.
+    pub fn id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    /// Reconfigures the surface for the new size of the window.
+    ///
+    /// # Arguments
+    /// * `device` - The device used for rendering.
+    /// * `size` - The new width and height of the window.
+    pub fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) {
+        if size.0 > 0 && size.1 > 0 {
+            self.config.width = size.0;
+            self.config.height = size.1;
+            self.surface.configure(device, &self.config);
+        }
+    }
+
+    /// Draws the content onto the surface, keeping its aspect ratio. Returns the surface
+    /// texture, which must be presented after the commands have been submitted, or `None` if
+    /// the frame is skipped for the mirror, e.g., because the surface is being reconfigured.
+    ///
+    /// # Arguments
+    /// * `device` - The device used for rendering.
+    /// * `encoder` - The encoder to record the commands into.
+    /// * `source` - The view of the offscreen target with the content.
+    /// * `source_size` - The width and height of the offscreen target.
+    pub fn draw(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        source: &wgpu::TextureView,
+        source_size: (u32, u32),
+    ) -> Result<Option<wgpu::SurfaceTexture>> {
+        let output = match self.surface.get_current_texture() {
+            Ok(output) => output,
+            Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                self.surface.configure(device, &self.config);
+                return Ok(None);
+            }
+            Err(wgpu::SurfaceError::Timeout) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let view = output
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = self.blitter.create_bind_group(device, source);
+
+        {
+            let mut pass = begin_surface_pass(encoder, &view, wgpu::Color::BLACK);
+
+            let v = letterboxed_viewport(source_size, (self.config.width, self.config.height));
+            pass.set_viewport(v.x, v.y, v.width, v.height, 0.0, 1.0);
+            self.blitter.draw(&mut pass, &bind_group);
+        }
+
+        Ok(Some(output))
+    }
+}
@@ -0,0 +1,61 @@
+use winit::monitor::MonitorHandle;
+
+/// A rectangle on the desktop in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MonitorArea {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorArea {
+    /// Returns the area covered by the given monitor.
+    ///
+    /// # Arguments
+    /// * `monitor` - The monitor to get the area for.
+    pub(crate) fn of_monitor(monitor: &MonitorHandle) -> Self {
+        let position = monitor.position();
+        let size = monitor.size();
+
+        Self {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        }
+    }
+}
+
+/// The properties of a monitor connected to the system.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    /// The human-readable name of the monitor, if the platform provides it.
+    pub name: Option<String>,
+    /// The area covered by the monitor on the desktop in physical pixels.
+    pub area: MonitorArea,
+    /// The scale factor for converting logical into physical pixels on this monitor.
+    pub scale_factor: f64,
+    /// The refresh rate of the current video mode in millihertz, e.g., 60000 for 60 Hz, if the
+    /// platform provides it.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// True if this is the primary monitor of the system.
+    pub is_primary: bool,
+}
+
+impl MonitorInfo {
+    /// Collects the properties of the given monitor.
+    ///
+    /// # Arguments
+    /// * `monitor` - The monitor to get the properties for.
+    /// * `primary` - The primary monitor of the system, if known.
+    pub(crate) fn of_monitor(monitor: &MonitorHandle, primary: Option<&MonitorHandle>) -> Self {
+        Self {
+            name: monitor.name(),
+            area: MonitorArea::of_monitor(monitor),
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+            is_primary: primary == Some(monitor),
+        }
+    }
+}
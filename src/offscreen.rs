@@ -0,0 +1,146 @@
+use crate::blit::Blitter;
+
+/// A texture the content is rendered into instead of the surface, which is then blitted onto
+/// the surface.
+pub(crate) struct OffscreenTarget {
+    size: (u32, u32),
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+impl OffscreenTarget {
+    /// Creates a new offscreen target.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `blitter` - The blitter used for drawing the target onto the surface.
+    /// * `format` - The format of the texture.
+    /// * `size` - The width and height of the texture.
+    pub fn new(
+        device: &wgpu::Device,
+        blitter: &Blitter,
+        format: wgpu::TextureFormat,
+        size: (u32, u32),
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = blitter.create_bind_group(device, &view);
+
+        Self {
+            size,
+            view,
+            bind_group,
+        }
+    }
+
+    /// Returns the width and height of the target.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the view of the target the content is rendered into.
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Returns the bind group for using the target as source of the blitter.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+/// Begins a render pass on the surface that clears it to the given color.
+///
+/// # Arguments
+/// * `encoder` - The encoder to record the pass into.
+/// * `surface_view` - The view of the current surface texture.
+/// * `clear_color` - The color to clear the surface to.
+pub(crate) fn begin_surface_pass<'a>(
+    encoder: &'a mut wgpu::CommandEncoder,
+    surface_view: &'a wgpu::TextureView,
+    clear_color: wgpu::Color,
+) -> wgpu::RenderPass<'a> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Surface Blit Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: surface_view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(clear_color),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    })
+}
+
+/// Offscreen target with the size of the surface, which keeps its contents between frames.
+pub(crate) struct PreservedTarget {
+    target: OffscreenTarget,
+    blitter: Blitter,
+    format: wgpu::TextureFormat,
+}
+
+impl PreservedTarget {
+    /// Creates the persistent target and the pipeline for copying it onto the surface.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the resources on.
+    /// * `format` - The format of the surface, which is also used for the target.
+    /// * `size` - The current width and height of the surface.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let blitter = Blitter::new(device, format, wgpu::FilterMode::Nearest);
+        let target = OffscreenTarget::new(device, &blitter, format, size);
+
+        Self {
+            target,
+            blitter,
+            format,
+        }
+    }
+
+    /// Returns the view of the target the content is rendered into.
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.target.view()
+    }
+
+    /// Recreates the target if the surface size changed, which discards its contents.
+    /// Returns true if the target has been recreated.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `size` - The new width and height of the surface.
+    pub fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) -> bool {
+        if self.target.size() == size {
+            return false;
+        }
+
+        self.target = OffscreenTarget::new(device, &self.blitter, self.format, size);
+        true
+    }
+
+    /// Copies the target onto the surface.
+    ///
+    /// # Arguments
+    /// * `encoder` - The encoder to record the commands into.
+    /// * `surface_view` - The view of the current surface texture.
+    pub fn present(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+        let mut pass = begin_surface_pass(encoder, surface_view, wgpu::Color::BLACK);
+        self.blitter.draw(&mut pass, self.target.bind_group());
+    }
+}
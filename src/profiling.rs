@@ -0,0 +1,274 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use log::error;
+
+use crate::error::{Error, Result};
+
+/// Determines where the timings of the frames are recorded to while profiling.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FrameProfiling {
+    /// The timings aren't recorded.
+    #[default]
+    Disabled,
+    /// The timing of each frame is passed to `EventHandler::frame_timing` right after the frame.
+    Callback,
+    /// The timings are written as CSV to the file at the given path after each frame. The file
+    /// is created when the canvas is created and flushed when the canvas stops.
+    Csv(PathBuf),
+}
+
+/// The timing of a single frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameTiming {
+    /// The index of the frame, see `RenderContext::frame_index`.
+    pub frame_index: u64,
+    /// The time the CPU spent on updating and rendering the frame, including acquiring and
+    /// presenting the surface texture.
+    pub cpu_time: Duration,
+    /// The time the GPU spent on the render pass of the last measured frame, if timestamp
+    /// queries are enabled and a measurement has been read back since the previous frame.
+    /// Measurements complete a few frames later, i.e., belong to an earlier frame, and each
+    /// one is reported once.
+    pub gpu_time: Option<Duration>,
+}
+
+/// The frame rate averaged over the recent frames, e.g., for an on-screen FPS counter, see
+/// `RenderContext::frame_stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    /// The average number of frames per second, or zero before the second frame.
+    pub fps: f32,
+    /// The average time between the start of two frames in milliseconds, or zero before the
+    /// second frame.
+    pub frame_time_ms: f32,
+    /// The index of the current frame, see `RenderContext::frame_index`.
+    pub frame_index: u64,
+}
+
+/// Averages the time between frames over a rolling window of the recent frames.
+pub(crate) struct FrameStatsTracker {
+    window: usize,
+    frame_times: VecDeque<f32>,
+}
+
+impl FrameStatsTracker {
+    /// Creates a tracker without any frames.
+    ///
+    /// # Arguments
+    /// * `window` - The number of frames the statistics are averaged over.
+    pub fn new(window: usize) -> Result<Self> {
+        if window == 0 {
+            return Err(Error::Internal(
+                "Invalid frame statistics window 0".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            window,
+            frame_times: VecDeque::with_capacity(window),
+        })
+    }
+
+    /// Adds the time between the previous and the current frame, dropping the oldest one
+    /// outside the window. A frame time of zero, i.e., of the first frame or the first frame
+    /// after a pause, is ignored.
+    ///
+    /// # Arguments
+    /// * `frame_time` - The time between the frames in seconds.
+    pub fn record(&mut self, frame_time: f32) {
+        if frame_time <= 0.0 {
+            return;
+        }
+
+        if self.frame_times.len() == self.window {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_times.push_back(frame_time);
+    }
+
+    /// Returns the statistics averaged over the recorded frames.
+    ///
+    /// # Arguments
+    /// * `frame_index` - The index of the current frame.
+    pub fn stats(&self, frame_index: u64) -> FrameStats {
+        let sum: f32 = self.frame_times.iter().sum();
+        if sum <= 0.0 {
+            return FrameStats {
+                frame_index,
+                ..FrameStats::default()
+            };
+        }
+
+        let average = sum / self.frame_times.len() as f32;
+        FrameStats {
+            fps: 1.0 / average,
+            frame_time_ms: average * 1000.0,
+            frame_index,
+        }
+    }
+}
+
+/// Records the timings of the frames according to the profiling mode.
+pub(crate) struct FrameProfiler {
+    profiling: FrameProfiling,
+    writer: Option<BufWriter<File>>,
+}
+
+impl FrameProfiler {
+    /// Creates the profiler and the CSV file with its header, if any.
+    ///
+    /// # Arguments
+    /// * `profiling` - The profiling mode.
+    pub fn new(profiling: FrameProfiling) -> Result<Self> {
+        let writer = match &profiling {
+            FrameProfiling::Csv(path) => {
+                let file = File::create(path).map_err(|e| {
+                    Error::IO(format!(
+                        "Failed to create frame timing file {}: {}",
+                        path.to_string_lossy(),
+                        e
+                    ))
+                })?;
+
+                let mut writer = BufWriter::new(file);
+                writeln!(writer, "frame_index,cpu_time_ms,gpu_time_ms")?;
+                Some(writer)
+            }
+            _ => None,
+        };
+
+        Ok(Self { profiling, writer })
+    }
+
+    /// Returns true if the timings of the frames are recorded.
+    pub fn is_enabled(&self) -> bool {
+        self.profiling != FrameProfiling::Disabled
+    }
+
+    /// Records the timing of a frame. Returns true if the timing has to be passed to the
+    /// handler.
+    ///
+    /// # Arguments
+    /// * `timing` - The timing of the frame.
+    pub fn record(&mut self, timing: FrameTiming) -> bool {
+        match self.profiling {
+            FrameProfiling::Disabled => false,
+            FrameProfiling::Callback => true,
+            FrameProfiling::Csv(_) => {
+                if let Some(writer) = &mut self.writer {
+                    if let Err(e) = write_timing(writer, &timing) {
+                        // stop writing instead of reporting the same error for every frame
+                        error!("Failed to write the frame timings: {}", e);
+                        self.writer = None;
+                    }
+                }
+
+                false
+            }
+        }
+    }
+
+    /// Flushes the timings written to the CSV file, if any, and closes it.
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the timing of a frame as a row of the CSV file.
+///
+/// # Arguments
+/// * `writer` - The writer of the CSV file.
+/// * `timing` - The timing of the frame.
+fn write_timing(writer: &mut impl Write, timing: &FrameTiming) -> std::io::Result<()> {
+    let gpu_time = timing
+        .gpu_time
+        .map(|t| format!("{:.3}", t.as_secs_f64() * 1000.0))
+        .unwrap_or_default();
+
+    writeln!(
+        writer,
+        "{},{:.3},{}",
+        timing.frame_index,
+        timing.cpu_time.as_secs_f64() * 1000.0,
+        gpu_time
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_stats_are_averaged_over_the_recorded_frames() {
+        let mut tracker = FrameStatsTracker::new(4).unwrap();
+        tracker.record(0.01);
+        tracker.record(0.03);
+
+        let stats = tracker.stats(2);
+        assert_eq!(stats.frame_index, 2);
+        assert!((stats.frame_time_ms - 20.0).abs() < 1e-3);
+        assert!((stats.fps - 50.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn frame_stats_ignore_zero_frame_times() {
+        let mut tracker = FrameStatsTracker::new(4).unwrap();
+        tracker.record(0.0);
+
+        let stats = tracker.stats(0);
+        assert_eq!(stats.fps, 0.0);
+        assert_eq!(stats.frame_time_ms, 0.0);
+
+        tracker.record(0.02);
+        tracker.record(0.0);
+        assert!((tracker.stats(2).frame_time_ms - 20.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn frame_stats_drop_frames_outside_the_window() {
+        let mut tracker = FrameStatsTracker::new(2).unwrap();
+        tracker.record(0.1);
+        tracker.record(0.01);
+        tracker.record(0.03);
+
+        assert!((tracker.stats(3).frame_time_ms - 20.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn frame_stats_window_must_not_be_empty() {
+        assert!(FrameStatsTracker::new(0).is_err());
+    }
+
+    #[test]
+    fn csv_profiler_writes_each_recorded_frame() {
+        let path = std::env::temp_dir().join("wgpu-prog-csv-profiler-timings.csv");
+        let mut profiler = FrameProfiler::new(FrameProfiling::Csv(path.clone())).unwrap();
+        for frame_index in 0..2 {
+            let passed = profiler.record(FrameTiming {
+                frame_index,
+                cpu_time: Duration::from_millis(2),
+                gpu_time: (frame_index > 0).then(|| Duration::from_millis(1)),
+            });
+            assert!(!passed);
+        }
+
+        profiler.flush().unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            content,
+            "frame_index,cpu_time_ms,gpu_time_ms\n0,2.000,\n1,2.000,1.000\n"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::mpsc;
+
+use crate::error::{Error, Result};
+
+/// A copy of a texture into a buffer, which is mapped asynchronously for reading the pixels
+/// on the CPU. The rows of the buffer are padded to `COPY_BYTES_PER_ROW_ALIGNMENT`.
+pub(crate) struct TextureReadback {
+    buffer: wgpu::Buffer,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    mapped: Option<mpsc::Receiver<std::result::Result<(), wgpu::BufferAsyncError>>>,
+}
+
+impl TextureReadback {
+    /// Records the copy of the first mip level of the given 2D texture into a new buffer.
+    /// The commands have to be submitted before calling `map`.
+    ///
+    /// # Arguments
+    /// * `device` - The device the texture has been created on.
+    /// * `encoder` - The encoder to record the copy into.
+    /// * `texture` - The texture with an uncompressed format and the `COPY_SRC` usage.
+    pub fn new(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Result<Self> {
+        let format = texture.format();
+        let bytes_per_pixel = match format.block_size(None) {
+            Some(block_size) if format.block_dimensions() == (1, 1) => block_size,
+            _ => {
+                return Err(Error::Internal(format!(
+                    "Cannot read back texture with format {:?}",
+                    format
+                )))
+            }
+        };
+
+        let (width, height) = (texture.width(), texture.height());
+        let unpadded_bytes_per_row = width * bytes_per_pixel;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Ok(Self {
+            buffer,
+            size: (width, height),
+            format,
+            unpadded_bytes_per_row,
+            padded_bytes_per_row,
+            mapped: None,
+        })
+    }
+
+    /// Returns the width and height of the copied texture.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the format of the copied texture.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Starts mapping the buffer, which completes once the GPU is done with the copy and the
+    /// device has been polled. Must be called after the copy has been submitted.
+    pub fn map(&mut self) {
+        let (sender, receiver) = mpsc::channel();
+        self.buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+
+        self.mapped = Some(receiver);
+    }
+
+    /// Returns the pixels as tightly packed rows, starting with the top row, or `None` if the
+    /// buffer hasn't been mapped yet.
+    pub fn try_read(&self) -> Option<Result<Vec<u8>>> {
+        let result = match self.mapped.as_ref()?.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return None,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                return Some(Err(Error::GraphicsAPI(
+                    "The readback buffer has been dropped before mapping it".to_string(),
+                )))
+            }
+        };
+
+        if let Err(e) = result {
+            return Some(Err(Error::GraphicsAPI(format!(
+                "Failed to map the readback buffer: {}",
+                e
+            ))));
+        }
+
+        // strip the padding of the rows
+        let pixels = self
+            .buffer
+            .slice(..)
+            .get_mapped_range()
+            .chunks(self.padded_bytes_per_row as usize)
+            .flat_map(|row| &row[..self.unpadded_bytes_per_row as usize])
+            .copied()
+            .collect();
+        self.buffer.unmap();
+
+        Some(Ok(pixels))
+    }
+}
+
+/// Copies the first mip level of the given 2D texture into memory and returns its pixels as
+/// tightly packed rows, starting with the top row. Blocks until the GPU is done.
+///
+/// # Arguments
+/// * `device` - The device the texture has been created on.
+/// * `queue` - The command queue of the device.
+/// * `texture` - The texture with an uncompressed format and the `COPY_SRC` usage.
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<Vec<u8>> {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Readback Encoder"),
+    });
+    let mut readback = TextureReadback::new(device, &mut encoder, texture)?;
+    queue.submit(std::iter::once(encoder.finish()));
+
+    readback.map();
+    device.poll(wgpu::Maintain::Wait);
+
+    readback.try_read().unwrap_or_else(|| {
+        Err(Error::GraphicsAPI(
+            "The readback buffer has not been mapped".to_string(),
+        ))
+    })
+}
+
+/// Converts the given tightly packed pixels into RGBA bytes. Only 8-bit RGBA and BGRA formats
+/// are supported.
+///
+/// # Arguments
+/// * `format` - The format of the pixels.
+/// * `pixels` - The pixels to convert.
+pub(crate) fn to_rgba(format: wgpu::TextureFormat, mut pixels: Vec<u8>) -> Result<Vec<u8>> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => Ok(pixels),
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+
+            Ok(pixels)
+        }
+        _ => Err(Error::Internal(format!(
+            "Cannot convert pixels with format {:?} to RGBA",
+            format
+        ))),
+    }
+}
@@ -0,0 +1,91 @@
+use crate::error::Error;
+
+/// The action taken by the canvas after rendering a frame failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Reconfigures the surface and continues with the next frame.
+    Reconfigure,
+    /// Drops the frame and continues with the next one.
+    Skip,
+    /// Recreates the device, the surface and the resources of the canvas, e.g., if the device
+    /// has been lost after a driver reset, and notifies `EventHandler::device_recovered` so the
+    /// handler can recreate its own resources. Exits like `Exit` if the recovery fails.
+    Recover,
+    /// Exits the event loop, and `create_and_run_canvas` returns the error.
+    Exit,
+}
+
+/// Determines how the canvas recovers from errors while rendering a frame. All errors are
+/// reported to `EventHandler::render_error` in addition, regardless of the policy.
+pub trait ErrorPolicy {
+    /// Is called if the surface has been lost. Reconfigures the surface by default, whereas
+    /// `RecoveryAction::Recover` also recreates the device if it has been lost with the surface.
+    ///
+    /// # Arguments
+    /// * `error` - The error that occurred.
+    fn on_context_lost(&mut self, _error: &Error) -> RecoveryAction {
+        RecoveryAction::Reconfigure
+    }
+
+    /// Is called if the surface no longer matches the window, e.g., after a resize raced with
+    /// acquiring the surface texture. Reconfigures the surface by default.
+    ///
+    /// # Arguments
+    /// * `error` - The error that occurred.
+    fn on_outdated(&mut self, _error: &Error) -> RecoveryAction {
+        RecoveryAction::Reconfigure
+    }
+
+    /// Is called if acquiring the surface texture timed out more often in a row than
+    /// `CanvasOptions::max_consecutive_timeouts` tolerates. Skips the frame by default.
+    ///
+    /// # Arguments
+    /// * `error` - The error that occurred.
+    fn on_timeout(&mut self, _error: &Error) -> RecoveryAction {
+        RecoveryAction::Skip
+    }
+
+    /// Is called if the GPU ran out of memory. Exits the event loop by default.
+    ///
+    /// # Arguments
+    /// * `error` - The error that occurred.
+    fn on_out_of_memory(&mut self, _error: &Error) -> RecoveryAction {
+        RecoveryAction::Exit
+    }
+
+    /// Is called for all other errors, which are usually resolved by the next frame. Skips the
+    /// frame by default.
+    ///
+    /// # Arguments
+    /// * `error` - The error that occurred.
+    fn on_error(&mut self, _error: &Error) -> RecoveryAction {
+        RecoveryAction::Skip
+    }
+}
+
+/// The error policy used by default, which reconfigures the surface if it has been lost or is
+/// outdated, exits if the GPU ran out of memory and skips the frame for all other errors.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultErrorPolicy;
+
+impl ErrorPolicy for DefaultErrorPolicy {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_policy_reconfigures_outdated_surface() {
+        let error = Error::from(wgpu::SurfaceError::Outdated);
+        assert_eq!(
+            DefaultErrorPolicy.on_outdated(&error),
+            RecoveryAction::Reconfigure
+        );
+    }
+
+    #[test]
+    fn default_policy_skips_timeouts() {
+        let error = Error::from(wgpu::SurfaceError::Timeout);
+        assert_eq!(DefaultErrorPolicy.on_timeout(&error), RecoveryAction::Skip);
+    }
+}
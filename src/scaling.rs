@@ -0,0 +1,311 @@
+use crate::{
+    blit::Blitter,
+    offscreen::{begin_surface_pass, OffscreenTarget},
+};
+
+/// The mode for mapping the rendered content onto the surface.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// The content is rendered directly into the surface at its native resolution.
+    #[default]
+    Native,
+
+    /// The content is rendered into a texture with the given fixed resolution, which is then
+    /// upscaled to the surface by the largest integer factor that fits using nearest-neighbor
+    /// filtering, e.g., for pixel-art. The remaining area of the surface is filled with black
+    /// bars. If the surface is smaller than the resolution, the content is scaled down to fit.
+    IntegerScaled { width: u32, height: u32 },
+
+    /// The content is rendered into a texture with the given aspect ratio, e.g., 16:9, which
+    /// covers the largest centered rectangle of the surface with this aspect ratio. The
+    /// remaining area of the surface is filled with black bars. The texture is resized with the
+    /// surface, i.e., the content is rendered at the native resolution of the rectangle.
+    Letterboxed {
+        aspect_width: u32,
+        aspect_height: u32,
+    },
+}
+
+/// A rectangle on the surface in physical pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Computes the viewport for showing content of the given size centered on the surface, scaled
+/// by the largest integer factor that fits. The viewport is at least one pixel large.
+///
+/// # Arguments
+/// * `content` - The width and height of the content.
+/// * `surface` - The width and height of the surface.
+pub(crate) fn integer_scaled_viewport(content: (u32, u32), surface: (u32, u32)) -> Viewport {
+    let scale_x = surface.0 as f32 / content.0 as f32;
+    let scale_y = surface.1 as f32 / content.1 as f32;
+    let scale = scale_x.min(scale_y);
+
+    // only scale down fractionally if the content doesn't fit at all
+    let scale = if scale >= 1.0 { scale.floor() } else { scale };
+
+    let width = (content.0 as f32 * scale).max(1.0);
+    let height = (content.1 as f32 * scale).max(1.0);
+
+    Viewport {
+        x: ((surface.0 as f32 - width) / 2.0).floor(),
+        y: ((surface.1 as f32 - height) / 2.0).floor(),
+        width,
+        height,
+    }
+}
+
+/// Computes the largest viewport with the given aspect ratio that fits centered onto the
+/// surface. The viewport is aligned to whole pixels and at least one pixel large.
+///
+/// # Arguments
+/// * `aspect` - The aspect ratio as width and height, e.g., (16, 9).
+/// * `surface` - The width and height of the surface.
+pub(crate) fn letterboxed_viewport(aspect: (u32, u32), surface: (u32, u32)) -> Viewport {
+    let scale_x = surface.0 as f32 / aspect.0 as f32;
+    let scale_y = surface.1 as f32 / aspect.1 as f32;
+    let scale = scale_x.min(scale_y);
+
+    let width = (aspect.0 as f32 * scale).floor().max(1.0);
+    let height = (aspect.1 as f32 * scale).floor().max(1.0);
+
+    Viewport {
+        x: ((surface.0 as f32 - width) / 2.0).floor(),
+        y: ((surface.1 as f32 - height) / 2.0).floor(),
+        width,
+        height,
+    }
+}
+
+/// Offscreen render target which is drawn onto a sub-rectangle of the surface, either with a
+/// fixed resolution upscaled by an integer factor or with a fixed aspect ratio.
+pub(crate) struct Scaler {
+    mode: ScalingMode,
+    format: wgpu::TextureFormat,
+    target: OffscreenTarget,
+    blitter: Blitter,
+    viewport: Viewport,
+}
+
+impl Scaler {
+    /// Creates the offscreen target and the pipeline for drawing it onto the surface.
+    /// Returns `None` in the native mode, which doesn't need an offscreen target.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the resources on.
+    /// * `format` - The format of the surface, which is also used for the offscreen target.
+    /// * `mode` - The scaling mode, whose dimensions must be non-zero.
+    /// * `surface_size` - The current width and height of the surface.
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        mode: ScalingMode,
+        surface_size: (u32, u32),
+    ) -> Option<Self> {
+        let viewport = Self::viewport_for(mode, surface_size)?;
+        let blitter = Blitter::new(device, format, wgpu::FilterMode::Nearest);
+        let target = OffscreenTarget::new(
+            device,
+            &blitter,
+            format,
+            Self::target_size_for(mode, &viewport),
+        );
+
+        Some(Self {
+            mode,
+            format,
+            target,
+            blitter,
+            viewport,
+        })
+    }
+
+    /// Returns the viewport on the surface for the given mode or `None` in the native mode.
+    fn viewport_for(mode: ScalingMode, surface_size: (u32, u32)) -> Option<Viewport> {
+        match mode {
+            ScalingMode::Native => None,
+            ScalingMode::IntegerScaled { width, height } => {
+                Some(integer_scaled_viewport((width, height), surface_size))
+            }
+            ScalingMode::Letterboxed {
+                aspect_width,
+                aspect_height,
+            } => Some(letterboxed_viewport(
+                (aspect_width, aspect_height),
+                surface_size,
+            )),
+        }
+    }
+
+    /// Returns the size of the offscreen target for the given mode and viewport, i.e., the
+    /// fixed resolution or the size of the viewport.
+    fn target_size_for(mode: ScalingMode, viewport: &Viewport) -> (u32, u32) {
+        match mode {
+            ScalingMode::IntegerScaled { width, height } => (width, height),
+            _ => (viewport.width as u32, viewport.height as u32),
+        }
+    }
+
+    /// Returns the scaling mode of the offscreen target.
+    pub fn mode(&self) -> ScalingMode {
+        self.mode
+    }
+
+    /// Returns the width and height of the offscreen target.
+    pub fn size(&self) -> (u32, u32) {
+        self.target.size()
+    }
+
+    /// Returns the view of the offscreen target the content is rendered into.
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.target.view()
+    }
+
+    /// Returns the rectangle of the surface the content is drawn onto.
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    /// Recomputes the viewport for the new surface size and recreates the offscreen target if
+    /// its size changes. Returns true if the offscreen target has been recreated.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the new offscreen target on.
+    /// * `surface_size` - The new width and height of the surface.
+    pub fn resize(&mut self, device: &wgpu::Device, surface_size: (u32, u32)) -> bool {
+        let viewport = match Self::viewport_for(self.mode, surface_size) {
+            Some(viewport) => viewport,
+            None => return false,
+        };
+        self.viewport = viewport;
+
+        let size = Self::target_size_for(self.mode, &viewport);
+        if size == self.target.size() {
+            return false;
+        }
+
+        self.target = OffscreenTarget::new(device, &self.blitter, self.format, size);
+        true
+    }
+
+    /// Clears the surface to black and draws the offscreen target onto the viewport.
+    ///
+    /// # Arguments
+    /// * `encoder` - The encoder to record the commands into.
+    /// * `surface_view` - The view of the current surface texture.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, surface_view: &wgpu::TextureView) {
+        let mut pass = begin_surface_pass(encoder, surface_view, wgpu::Color::BLACK);
+
+        let v = &self.viewport;
+        pass.set_viewport(v.x, v.y, v.width, v.height, 0.0, 1.0);
+        self.blitter.draw(&mut pass, self.target.bind_group());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integer_scaled_viewport_fills_exact_multiple() {
+        let v = integer_scaled_viewport((320, 180), (960, 540));
+        assert_eq!(
+            v,
+            Viewport {
+                x: 0.0,
+                y: 0.0,
+                width: 960.0,
+                height: 540.0
+            }
+        );
+    }
+
+    #[test]
+    fn integer_scaled_viewport_uses_largest_integer_scale() {
+        // 3.5x fits horizontally and 3x vertically, so the content is scaled by 3
+        let v = integer_scaled_viewport((320, 180), (1120, 560));
+        assert_eq!((v.width, v.height), (960.0, 540.0));
+        assert_eq!((v.x, v.y), (80.0, 10.0));
+    }
+
+    #[test]
+    fn integer_scaled_viewport_never_scales_below_one_if_content_fits() {
+        let v = integer_scaled_viewport((320, 180), (639, 359));
+        assert_eq!((v.width, v.height), (320.0, 180.0));
+    }
+
+    #[test]
+    fn integer_scaled_viewport_fits_smaller_surface() {
+        // the viewport must stay within the surface, so the content is scaled down to fit
+        let v = integer_scaled_viewport((320, 180), (160, 120));
+        assert!(v.width > 0.0 && v.height > 0.0);
+        assert!(v.x >= 0.0 && v.y >= 0.0);
+        assert!(v.x + v.width <= 160.0 && v.y + v.height <= 120.0);
+        assert_eq!(v.width, 160.0);
+    }
+
+    #[test]
+    fn integer_scaled_viewport_is_never_empty() {
+        let v = integer_scaled_viewport((320, 180), (0, 0));
+        assert_eq!((v.width, v.height), (1.0, 1.0));
+
+        let v = integer_scaled_viewport((320, 180), (1, 1));
+        assert!(v.width >= 1.0 && v.height >= 1.0);
+    }
+
+    #[test]
+    fn letterboxed_viewport_adds_bars_left_and_right_for_wider_surface() {
+        let v = letterboxed_viewport((16, 9), (2000, 900));
+        assert_eq!(
+            v,
+            Viewport {
+                x: 200.0,
+                y: 0.0,
+                width: 1600.0,
+                height: 900.0
+            }
+        );
+    }
+
+    #[test]
+    fn letterboxed_viewport_adds_bars_top_and_bottom_for_taller_surface() {
+        let v = letterboxed_viewport((16, 9), (1600, 1200));
+        assert_eq!(
+            v,
+            Viewport {
+                x: 0.0,
+                y: 150.0,
+                width: 1600.0,
+                height: 900.0
+            }
+        );
+    }
+
+    #[test]
+    fn letterboxed_viewport_fills_matching_surface() {
+        let v = letterboxed_viewport((4, 3), (800, 600));
+        assert_eq!((v.x, v.y, v.width, v.height), (0.0, 0.0, 800.0, 600.0));
+    }
+
+    #[test]
+    fn letterboxed_viewport_is_never_empty() {
+        let v = letterboxed_viewport((16, 9), (0, 0));
+        assert_eq!((v.width, v.height), (1.0, 1.0));
+
+        let v = letterboxed_viewport((16, 9), (0, 600));
+        assert_eq!((v.width, v.height), (1.0, 1.0));
+    }
+
+    #[test]
+    fn integer_scaled_viewport_centers_on_whole_pixels() {
+        // one spare pixel horizontally and three vertically
+        let v = integer_scaled_viewport((160, 90), (321, 183));
+        assert_eq!((v.width, v.height), (320.0, 180.0));
+        assert_eq!((v.x, v.y), (0.0, 1.0));
+    }
+}
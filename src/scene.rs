@@ -0,0 +1,46 @@
+use crate::{
+    blit::Blitter,
+    offscreen::{begin_surface_pass, OffscreenTarget},
+};
+
+/// Offscreen target with a separate resolution for the scene, which is stretched over the
+/// whole target of the frame before the overlay is rendered on top at the native resolution.
+pub(crate) struct SceneTarget {
+    target: OffscreenTarget,
+    blitter: Blitter,
+}
+
+impl SceneTarget {
+    /// Creates the scene target and the pipeline for compositing it.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the resources on.
+    /// * `format` - The format of the surface, which is also used for the scene target.
+    /// * `size` - The width and height of the scene target.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, size: (u32, u32)) -> Self {
+        let blitter = Blitter::new(device, format, wgpu::FilterMode::Linear);
+        let target = OffscreenTarget::new(device, &blitter, format, size);
+
+        Self { target, blitter }
+    }
+
+    /// Returns the width and height of the scene target.
+    pub fn size(&self) -> (u32, u32) {
+        self.target.size()
+    }
+
+    /// Returns the view of the scene target the scene is rendered into.
+    pub fn view(&self) -> &wgpu::TextureView {
+        self.target.view()
+    }
+
+    /// Draws the scene stretched over the whole given target.
+    ///
+    /// # Arguments
+    /// * `encoder` - The encoder to record the commands into.
+    /// * `target` - The view of the target of the frame.
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, target: &wgpu::TextureView) {
+        let mut pass = begin_surface_pass(encoder, target, wgpu::Color::BLACK);
+        self.blitter.draw(&mut pass, self.target.bind_group());
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// The WGSL source of the full-screen vertex shader, see `create_fullscreen_vertex_shader`.
+pub const FULLSCREEN_VERTEX_SHADER: &str = include_str!("shader/fullscreen.wgsl");
+
+/// The entry point of the full-screen vertex shader.
+pub const FULLSCREEN_VERTEX_ENTRY_POINT: &str = "vs_main";
+
+/// Creates the vertex shader for drawing a full-screen triangle without any vertex buffer, so
+/// only a fragment shader has to be written, e.g., for post-processing. The vertex shader
+/// outputs the texture coordinates of the fragment at `@location(0)` as `vec2<f32>`, with
+/// (0, 0) being the top-left corner. The entry point is `FULLSCREEN_VERTEX_ENTRY_POINT`, and
+/// the pipeline must not have any vertex buffers. See `draw_fullscreen`.
+///
+/// # Arguments
+/// * `device` - The device to create the shader module on.
+pub fn create_fullscreen_vertex_shader(device: &wgpu::Device) -> wgpu::ShaderModule {
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Fullscreen Vertex Shader"),
+        source: wgpu::ShaderSource::Wgsl(FULLSCREEN_VERTEX_SHADER.into()),
+    })
+}
+
+/// Draws a full-screen triangle with the given pipeline, whose vertex stage is the full-screen
+/// vertex shader. The bind groups of the pipeline have to be set by the caller.
+///
+/// # Arguments
+/// * `pass` - The render pass to draw into.
+/// * `pipeline` - The pipeline created with `create_fullscreen_vertex_shader`.
+pub fn draw_fullscreen<'a>(pass: &mut wgpu::RenderPass<'a>, pipeline: &'a wgpu::RenderPipeline) {
+    pass.set_pipeline(pipeline);
+    pass.draw(0..3, 0..1);
+}
+
+/// Compiles the given WGSL source into a shader module. Unlike `Device::create_shader_module`,
+/// compile errors are returned instead of being reported to the uncaptured error handler,
+/// which panics by default.
+///
+/// # Arguments
+/// * `device` - The device to create the shader module on.
+/// * `label` - The debug label of the shader module.
+/// * `source` - The WGSL source code.
+pub fn create_shader_module(
+    device: &wgpu::Device,
+    label: Option<&str>,
+    source: &str,
+) -> Result<wgpu::ShaderModule> {
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label,
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(error) => Err(Error::GraphicsAPI(format!(
+            "Failed to compile shader {}: {}",
+            label.unwrap_or("<unnamed>"),
+            error
+        ))),
+        None => Ok(module),
+    }
+}
+
+/// Loads the WGSL shader at the given path and compiles it into a shader module.
+///
+/// # Arguments
+/// * `device` - The device to create the shader module on.
+/// * `path` - The path of the WGSL file.
+pub fn load_shader(device: &wgpu::Device, path: &Path) -> Result<wgpu::ShaderModule> {
+    let source = std::fs::read_to_string(path).map_err(|e| {
+        Error::IO(format!(
+            "Failed to read shader {}: {}",
+            path.to_string_lossy(),
+            e
+        ))
+    })?;
+
+    create_shader_module(device, Some(&path.to_string_lossy()), &source)
+}
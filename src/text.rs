@@ -0,0 +1,193 @@
+use std::{cell::RefCell, sync::Arc};
+
+use glyphon::{
+    fontdb, Attrs, Buffer, Color, Family, FontSystem, Metrics, Resolution, Shaping, SwashCache,
+    TextArea, TextAtlas, TextBounds, TextRenderer,
+};
+
+use crate::error::{Error, Result};
+
+/// The font used for all text, which is bundled so the text looks the same on every system.
+const FONT: &[u8] = include_bytes!("fonts/FiraMono-Medium.ttf");
+
+/// A text which has been queued for drawing in the overlay pass.
+struct QueuedText {
+    text: String,
+    position: (f32, f32),
+    size: f32,
+    color: wgpu::Color,
+}
+
+/// The state needed for laying out and rasterizing the glyphs.
+struct TextState {
+    font_system: FontSystem,
+    swash_cache: SwashCache,
+    atlas: TextAtlas,
+    renderer: TextRenderer,
+}
+
+/// Collects the texts drawn by the handler during a frame and renders them on top of the
+/// content of the frame.
+pub(crate) struct TextOverlay {
+    queued: RefCell<Vec<QueuedText>>,
+    state: RefCell<TextState>,
+}
+
+impl TextOverlay {
+    /// Creates a new text overlay.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the glyph atlas and the pipeline on.
+    /// * `queue` - The command queue of the device.
+    /// * `format` - The format of the texture the text is rendered into.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
+        // only the bundled font is loaded, as loading the system fonts takes a long time
+        let mut db = fontdb::Database::new();
+        db.load_font_source(fontdb::Source::Binary(Arc::new(FONT)));
+        db.set_monospace_family("Fira Mono");
+
+        let font_system = FontSystem::new_with_locale_and_db("en-US".to_string(), db);
+        let mut atlas = TextAtlas::new(device, queue, format);
+        let renderer =
+            TextRenderer::new(&mut atlas, device, wgpu::MultisampleState::default(), None);
+
+        Self {
+            queued: RefCell::new(Vec::new()),
+            state: RefCell::new(TextState {
+                font_system,
+                swash_cache: SwashCache::new(),
+                atlas,
+                renderer,
+            }),
+        }
+    }
+
+    /// Queues the given text for drawing in the next overlay pass.
+    ///
+    /// # Arguments
+    /// * `text` - The text, which may span multiple lines.
+    /// * `position` - The top-left corner of the text in pixels.
+    /// * `size` - The font size in pixels.
+    /// * `color` - The color of the text.
+    pub fn queue(&self, text: &str, position: (f32, f32), size: f32, color: wgpu::Color) {
+        self.queued.borrow_mut().push(QueuedText {
+            text: text.to_string(),
+            position,
+            size,
+            color,
+        });
+    }
+
+    /// Renders all queued texts on top of the given target and clears the queue.
+    /// Does nothing if no text has been queued.
+    ///
+    /// # Arguments
+    /// * `device` - The device used for rendering.
+    /// * `queue` - The command queue of the device.
+    /// * `encoder` - The encoder to record the overlay pass into.
+    /// * `target` - The view of the texture the text is rendered into.
+    /// * `size` - The width and height of the target in pixels.
+    pub fn render(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        size: (u32, u32),
+    ) -> Result<()> {
+        let queued = self.queued.take();
+        if queued.is_empty() {
+            return Ok(());
+        }
+
+        let state = &mut *self.state.borrow_mut();
+
+        let buffers: Vec<Buffer> = queued
+            .iter()
+            .map(|text| {
+                let mut buffer = Buffer::new(
+                    &mut state.font_system,
+                    Metrics::new(text.size, text.size * 1.2),
+                );
+                buffer.set_size(&mut state.font_system, size.0 as f32, size.1 as f32);
+                buffer.set_text(
+                    &mut state.font_system,
+                    &text.text,
+                    Attrs::new().family(Family::Monospace),
+                    Shaping::Basic,
+                );
+                buffer.shape_until_scroll(&mut state.font_system);
+                buffer
+            })
+            .collect();
+
+        let areas = queued.iter().zip(&buffers).map(|(text, buffer)| TextArea {
+            buffer,
+            left: text.position.0,
+            top: text.position.1,
+            scale: 1.0,
+            bounds: TextBounds {
+                left: 0,
+                top: 0,
+                right: size.0 as i32,
+                bottom: size.1 as i32,
+            },
+            default_color: to_glyphon_color(text.color),
+        });
+
+        state
+            .renderer
+            .prepare(
+                device,
+                queue,
+                &mut state.font_system,
+                &mut state.atlas,
+                Resolution {
+                    width: size.0,
+                    height: size.1,
+                },
+                areas,
+                &mut state.swash_cache,
+            )
+            .map_err(|e| Error::GraphicsAPI(format!("Failed to prepare text: {}", e)))?;
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Text Overlay Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            state
+                .renderer
+                .render(&state.atlas, &mut render_pass)
+                .map_err(|e| Error::GraphicsAPI(format!("Failed to render text: {}", e)))?;
+        }
+
+        // free the glyphs which haven't been used in this frame
+        state.atlas.trim();
+
+        Ok(())
+    }
+}
+
+/// Converts the given color with components in [0, 1] into a glyphon color.
+fn to_glyphon_color(color: wgpu::Color) -> Color {
+    let channel = |value: f64| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    Color::rgba(
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    )
+}
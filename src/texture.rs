@@ -0,0 +1,314 @@
+use crate::error::{Error, Result};
+
+/// A texture together with the default view for binding it in shaders.
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+/// The options shared by all texture helpers.
+#[derive(Clone, Debug)]
+pub struct TextureOptions<'a> {
+    /// The debug label of the texture.
+    pub label: Option<&'a str>,
+    /// The format of the texture.
+    pub format: wgpu::TextureFormat,
+    /// The usages of the texture. `COPY_DST` is added automatically when data is uploaded.
+    pub usage: wgpu::TextureUsages,
+    /// If true, the texture gets a full mip-chain down to 1x1, see `mip_level_count`.
+    /// Otherwise, it only has a single mip level.
+    pub mipmapped: bool,
+}
+
+impl Default for TextureOptions<'_> {
+    fn default() -> Self {
+        Self {
+            label: None,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING,
+            mipmapped: false,
+        }
+    }
+}
+
+/// Returns the number of mip levels of a full mip-chain for a 2D texture with the given size,
+/// i.e., the number of times the larger side can be halved until it reaches one pixel.
+///
+/// # Arguments
+/// * `width` - The width of the texture.
+/// * `height` - The height of the texture.
+pub fn mip_level_count(width: u32, height: u32) -> u32 {
+    wgpu::Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+    }
+    .max_mips(wgpu::TextureDimension::D2)
+}
+
+impl Texture {
+    /// Creates an empty 2D texture.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `width` - The width of the texture.
+    /// * `height` - The height of the texture.
+    /// * `options` - The options for the texture.
+    pub fn new_2d(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        options: &TextureOptions,
+    ) -> Result<Self> {
+        Self::create(
+            device,
+            width,
+            height,
+            1,
+            options,
+            options.usage,
+            wgpu::TextureViewDimension::D2,
+        )
+    }
+
+    /// Creates an empty 2D texture array, e.g., for shadow cascades.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `width` - The width of each layer.
+    /// * `height` - The height of each layer.
+    /// * `layers` - The number of layers.
+    /// * `options` - The options for the texture.
+    pub fn new_2d_array(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        layers: u32,
+        options: &TextureOptions,
+    ) -> Result<Self> {
+        Self::create(
+            device,
+            width,
+            height,
+            layers,
+            options,
+            options.usage,
+            wgpu::TextureViewDimension::D2Array,
+        )
+    }
+
+    /// Creates an empty cubemap with six square faces, e.g., for skyboxes.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `size` - The width and height of each face.
+    /// * `options` - The options for the texture.
+    pub fn new_cube(device: &wgpu::Device, size: u32, options: &TextureOptions) -> Result<Self> {
+        Self::create(
+            device,
+            size,
+            size,
+            6,
+            options,
+            options.usage,
+            wgpu::TextureViewDimension::Cube,
+        )
+    }
+
+    /// Creates a cubemap and uploads the data of its six faces into the first mip level.
+    /// The faces must be given in the order +X, -X, +Y, -Y, +Z, -Z, each tightly packed in the
+    /// given uncompressed format.
+    ///
+    /// # Arguments
+    /// * `device` - The device to create the texture on.
+    /// * `queue` - The queue for uploading the face data.
+    /// * `size` - The width and height of each face.
+    /// * `faces` - The pixel data of the six faces.
+    /// * `options` - The options for the texture.
+    pub fn from_cube_faces(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        size: u32,
+        faces: [&[u8]; 6],
+        options: &TextureOptions,
+    ) -> Result<Self> {
+        let bytes_per_row = check_cube_faces(size, &faces, options.format)?;
+
+        let cube = Self::create(
+            device,
+            size,
+            size,
+            6,
+            options,
+            options.usage | wgpu::TextureUsages::COPY_DST,
+            wgpu::TextureViewDimension::Cube,
+        )?;
+
+        for (layer, face) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &cube.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                face,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(cube)
+    }
+
+    /// Validates the parameters and creates the texture with a view of the given dimension.
+    fn create(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        layers: u32,
+        options: &TextureOptions,
+        usage: wgpu::TextureUsages,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Result<Self> {
+        let limits = device.limits();
+
+        if width == 0 || height == 0 || layers == 0 {
+            return Err(Error::Internal(format!(
+                "Invalid texture size {}x{} with {} layers",
+                width, height, layers
+            )));
+        }
+
+        if width > limits.max_texture_dimension_2d || height > limits.max_texture_dimension_2d {
+            return Err(Error::GraphicsAPI(format!(
+                "Texture size {}x{} exceeds the maximum dimension {}",
+                width, height, limits.max_texture_dimension_2d
+            )));
+        }
+
+        if layers > limits.max_texture_array_layers {
+            return Err(Error::GraphicsAPI(format!(
+                "Texture with {} layers exceeds the maximum of {} layers",
+                layers, limits.max_texture_array_layers
+            )));
+        }
+
+        let (block_width, block_height) = options.format.block_dimensions();
+        if !width.is_multiple_of(block_width) || !height.is_multiple_of(block_height) {
+            return Err(Error::Internal(format!(
+                "Texture size {}x{} is not a multiple of the block size {}x{} of format {:?}",
+                width, height, block_width, block_height, options.format
+            )));
+        }
+
+        let mip_level_count = if options.mipmapped {
+            mip_level_count(width, height)
+        } else {
+            1
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: options.label,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: options.format,
+            usage,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: options.label,
+            dimension: Some(view_dimension),
+            array_layer_count: Some(layers),
+            ..Default::default()
+        });
+
+        Ok(Self { texture, view })
+    }
+}
+
+/// Validates the data of the cubemap faces and returns the number of bytes per row of a face.
+///
+/// # Arguments
+/// * `size` - The width and height of each face.
+/// * `faces` - The pixel data of the six faces.
+/// * `format` - The format of the cubemap.
+fn check_cube_faces(size: u32, faces: &[&[u8]; 6], format: wgpu::TextureFormat) -> Result<u32> {
+    let bytes_per_pixel = match format.block_size(None) {
+        Some(block_size) if format.block_dimensions() == (1, 1) => block_size,
+        _ => {
+            return Err(Error::Internal(format!(
+                "Cannot upload cubemap faces with format {:?}, an uncompressed color format is required",
+                format
+            )))
+        }
+    };
+
+    let bytes_per_row = size.checked_mul(bytes_per_pixel).ok_or_else(|| {
+        Error::Internal(format!(
+            "Cubemap faces of {}x{} pixels are too large",
+            size, size
+        ))
+    })?;
+
+    // computed in 64 bits, as the size of a face may exceed the 32 bits of usize on wasm
+    let face_size = bytes_per_row as u64 * size as u64;
+    for (index, face) in faces.iter().enumerate() {
+        if face.len() as u64 != face_size {
+            return Err(Error::Internal(format!(
+                "Cubemap face {} has {} bytes, but {} bytes are required for {}x{} pixels",
+                index,
+                face.len(),
+                face_size,
+                size,
+                size
+            )));
+        }
+    }
+
+    Ok(bytes_per_row)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_faces_must_match_the_face_size() {
+        let face = [0u8; 2 * 2 * 4];
+        let faces = [&face[..]; 6];
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        assert_eq!(check_cube_faces(2, &faces, format).unwrap(), 8);
+
+        let short = [0u8; 2 * 2 * 4 - 1];
+        let mut faces = [&face[..]; 6];
+        faces[3] = &short;
+        assert!(check_cube_faces(2, &faces, format).is_err());
+    }
+
+    #[test]
+    fn cube_faces_reject_oversized_and_compressed_formats() {
+        let faces = [&[][..]; 6];
+        assert!(check_cube_faces(u32::MAX, &faces, wgpu::TextureFormat::Rgba8Unorm).is_err());
+        assert!(check_cube_faces(4, &faces, wgpu::TextureFormat::Bc1RgbaUnorm).is_err());
+    }
+}